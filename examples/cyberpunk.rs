@@ -97,7 +97,7 @@ pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: Eve
     let y_pid = x_pid.with_integral_limit(Limit::new(-25., 25.));
     let offset = Vec2::new(200., 0.);
     let camera = commands
-        .spawn((Camera2d::default(), Transform::from_translation(offset.extend(0.))))
+        .spawn((Camera2d, Transform::from_translation(offset.extend(0.))))
         //.insert(CameraFollow::fixed(player).with_offset(offset))
         //.insert(CameraFollow::proportional(player, 0.1).with_offset(offset))
         .insert(CameraFollow::pid_xyz(player, &x_pid, &y_pid, &x_pid).with_offset(offset))
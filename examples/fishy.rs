@@ -1,6 +1,5 @@
 use bevy::prelude::*;
-use bevy_parallax::{CreateParallaxEvent, LayerData, ParallaxCameraComponent, ParallaxMoveEvent, ParallaxPlugin, ParallaxSystems};
-use ron::de::from_bytes;
+use bevy_parallax::{ParallaxAssetComponent, ParallaxCameraComponent, ParallaxMoveEvent, ParallaxPlugin, ParallaxSystems};
 
 fn main() {
     // Define window
@@ -27,16 +26,13 @@ fn main() {
         .run();
 }
 
-// Put a ParallaxCameraComponent on the camera used for parallax
-pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: EventWriter<CreateParallaxEvent>) {
-    let camera = commands
-        .spawn(Camera2d::default())
+// Put a ParallaxCameraComponent on the camera used for parallax and point it to the layers file.
+// Enable bevy's `file_watcher` feature to rebuild the layers when the file is edited.
+pub fn initialize_camera_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(Camera2d)
         .insert(ParallaxCameraComponent::default())
-        .id();
-    create_parallax.send(CreateParallaxEvent {
-        layers_data: from_bytes::<Vec<LayerData>>(include_bytes!("../data/fishy_layer_data.ron")).unwrap(),
-        camera: camera,
-    });
+        .insert(ParallaxAssetComponent::new(asset_server.load("fishy.parallax.ron")));
 }
 
 // Send a ParallaxMoveEvent with the desired camera movement speed
@@ -50,13 +46,13 @@ pub fn move_camera_system(
         move_event_writer.send(ParallaxMoveEvent {
             translation: Vec2::new(3.0, 0.0),
             rotation: 0.,
-            camera: camera,
+            camera,
         });
     } else if keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft) {
         move_event_writer.send(ParallaxMoveEvent {
             translation: Vec2::new(-3.0, 0.0),
             rotation: 0.,
-            camera: camera,
+            camera,
         });
     }
 }
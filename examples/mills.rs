@@ -2,8 +2,8 @@ use bevy::prelude::*;
 #[cfg(feature = "bevy-inspector-egui")]
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_parallax::{
-    Animation, CreateParallaxEvent, LayerData, LayerRepeat, LayerSpeed, ParallaxCameraComponent, ParallaxMoveEvent, ParallaxPlugin,
-    ParallaxSystems, RepeatStrategy,
+    Animation, LayerData, LayerRepeat, LayerSpeed, ParallaxBackground, ParallaxMoveEvent, ParallaxPlugin, ParallaxSystems,
+    RepeatStrategy,
 };
 
 fn main() {
@@ -35,14 +35,10 @@ fn main() {
     app.run();
 }
 
-// Put a ParallaxCameraComponent on the camera used for parallax
-pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: EventWriter<CreateParallaxEvent>) {
-    let camera = commands
-        .spawn(Camera2d::default())
-        .insert(ParallaxCameraComponent::default())
-        .id();
-    create_parallax.send(CreateParallaxEvent {
-        layers_data: vec![
+// Put a ParallaxBackground on the camera used for parallax
+pub fn initialize_camera_system(mut commands: Commands) {
+    commands.spawn(Camera2d).insert(ParallaxBackground {
+        layers: vec![
             LayerData {
                 speed: LayerSpeed::Bidirectional(0.99, 0.99),
                 repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
@@ -116,7 +112,6 @@ pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: Eve
                 ..default()
             },
         ],
-        camera: camera,
     });
 }
 
@@ -149,7 +144,7 @@ pub fn move_camera_system(
     }
     move_event_writer.send(ParallaxMoveEvent {
         translation: direction.normalize_or_zero() * speed,
-        camera: camera,
+        camera,
         rotation: 0.,
     });
 }
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use bevy_parallax::{
-    CreateParallaxEvent, LayerData, LayerSpeed, ParallaxCameraComponent, ParallaxMoveEvent, ParallaxPlugin, ParallaxSystems,
+    CreateParallaxEvent, DespawnParallaxEvent, LayerData, LayerSpeed, ParallaxCameraComponent, ParallaxExport, ParallaxLayerEvent,
+    ParallaxLayers, ParallaxMoveEvent, ParallaxPlugin, ParallaxSystems,
 };
 
 fn main() {
@@ -25,7 +26,7 @@ fn main() {
         .add_plugins(ParallaxPlugin)
         .add_systems(Startup, initialize_camera_system)
         .add_systems(Update, move_camera_system.before(ParallaxSystems))
-        .add_systems(Update, (reload_system, despawn_all))
+        .add_systems(Update, (reload_system, despawn_all, export_system, toggle_layer_system))
         .run();
 }
 
@@ -63,14 +64,14 @@ pub fn new_create_parallax_event(camera: Entity) -> CreateParallaxEvent {
                 ..Default::default()
             },
         ],
-        camera: camera,
+        camera,
     }
 }
 
 // Put a ParallaxCameraComponent on the camera used for parallax
 pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: EventWriter<CreateParallaxEvent>) {
     let camera = commands
-        .spawn(Camera2d::default())
+        .spawn(Camera2d)
         .insert(ParallaxCameraComponent::default())
         .id();
     create_parallax.send(new_create_parallax_event(camera));
@@ -88,14 +89,54 @@ pub fn reload_system(
 }
 
 pub fn despawn_all(
-    mut commands: Commands,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    all_query: Query<Entity, (Without<Camera>, Without<Window>)>,
+    camera_query: Query<Entity, With<Camera>>,
+    mut despawn_parallax: EventWriter<DespawnParallaxEvent>,
 ) {
+    let camera = camera_query.get_single().unwrap();
     if keyboard_input.just_released(KeyCode::KeyQ) {
-        for entity in all_query.iter() {
-            commands.entity(entity).despawn_recursive();
-        }
+        despawn_parallax.send(DespawnParallaxEvent { camera });
+    }
+}
+
+// Add or remove a single layer without recreating the others
+pub fn toggle_layer_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    camera_query: Query<Entity, With<Camera>>,
+    layers: ParallaxLayers,
+    mut layer_events: EventWriter<ParallaxLayerEvent>,
+) {
+    let camera = camera_query.get_single().unwrap();
+    if !keyboard_input.just_released(KeyCode::KeyL) {
+        return;
+    }
+    if layers.get(camera, "stars").is_some() {
+        layer_events.send(ParallaxLayerEvent::Remove {
+            camera,
+            id: "stars".to_string(),
+        });
+    } else {
+        layer_events.send(ParallaxLayerEvent::Insert {
+            camera,
+            layer: LayerData {
+                id: Some("stars".to_string()),
+                speed: LayerSpeed::Horizontal(0.95),
+                path: "sky-stars.png".to_string(),
+                scale: Vec2::splat(3.0),
+                z: 0.5,
+                ..Default::default()
+            },
+        });
+    }
+}
+
+// Write the current layers to a RON file that can be loaded as a ParallaxAsset
+pub fn export_system(keyboard_input: Res<ButtonInput<KeyCode>>, camera_query: Query<Entity, With<Camera>>, export: ParallaxExport) {
+    let camera = camera_query.get_single().unwrap();
+    if keyboard_input.just_released(KeyCode::KeyE) {
+        let ron = export.asset(camera).to_ron().unwrap();
+        std::fs::write("cyberpunk.parallax.ron", ron).unwrap();
+        info!("Parallax exported to cyberpunk.parallax.ron");
     }
 }
 
@@ -110,13 +151,13 @@ pub fn move_camera_system(
         move_event_writer.send(ParallaxMoveEvent {
             translation: Vec2::new(3.0, 0.0),
             rotation: 0.,
-            camera: camera,
+            camera,
         });
     } else if keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft) {
         move_event_writer.send(ParallaxMoveEvent {
             translation: Vec2::new(-3.0, 0.0),
             rotation: 0.,
-            camera: camera,
+            camera,
         });
     }
 }
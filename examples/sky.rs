@@ -28,10 +28,11 @@ fn main() {
         .run();
 }
 
-// Put a ParallaxCameraComponent on the camera used for parallax
+// Put a ParallaxCameraComponent on the camera used for parallax.
+// The tile sizes are not set, so they are inferred from the images once they are loaded.
 pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: EventWriter<CreateParallaxEvent>) {
     let camera = commands
-        .spawn(Camera2d::default())
+        .spawn(Camera2d)
         .insert(ParallaxCameraComponent::default())
         .id();
     create_parallax.send(CreateParallaxEvent {
@@ -39,7 +40,6 @@ pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: Eve
             LayerData {
                 speed: LayerSpeed::Bidirectional(0.9, 0.9),
                 path: "sky-stars.png".to_string(),
-                tile_size: UVec2::new(53, 55),
                 cols: 1,
                 rows: 1,
                 scale: Vec2::splat(3.0),
@@ -49,15 +49,16 @@ pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: Eve
             LayerData {
                 speed: LayerSpeed::Bidirectional(0.1, 0.5),
                 path: "sky-clouds.png".to_string(),
-                tile_size: UVec2::new(109, 43),
                 cols: 1,
                 rows: 1,
                 scale: Vec2::splat(4.0),
+                // Clouds drift even when the camera doesn't move
+                scroll: Vec2::new(-20.0, 0.0),
                 z: 1.0,
                 ..default()
             },
         ],
-        camera: camera,
+        camera,
     });
 }
 
@@ -72,26 +73,26 @@ pub fn move_camera_system(
         move_event_writer.send(ParallaxMoveEvent {
             translation: Vec2::new(3.0, 0.0),
             rotation: 0.,
-            camera: camera,
+            camera,
         });
     } else if keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft) {
         move_event_writer.send(ParallaxMoveEvent {
             translation: Vec2::new(-3.0, 0.0),
             rotation: 0.,
-            camera: camera,
+            camera,
         });
     }
     if keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp) {
         move_event_writer.send(ParallaxMoveEvent {
             translation: Vec2::new(0.0, 3.0),
             rotation: 0.,
-            camera: camera,
+            camera,
         });
     } else if keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown) {
         move_event_writer.send(ParallaxMoveEvent {
             translation: Vec2::new(0.0, -3.0),
             rotation: 0.,
-            camera: camera,
+            camera,
         });
     }
 }
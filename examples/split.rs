@@ -36,7 +36,7 @@ fn main() {
 pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: EventWriter<CreateParallaxEvent>) {
     let left_camera = commands
         .spawn((
-            Camera2d::default(),
+            Camera2d,
             Camera {
                 order: 0,
                 viewport: Some(Viewport {
@@ -52,7 +52,7 @@ pub fn initialize_camera_system(mut commands: Commands, mut create_parallax: Eve
         .insert(InputMap::awsd())
         .id();
     let right_camera = commands
-        .spawn((Camera2d::default() ,
+        .spawn((Camera2d ,
             Camera {
                 order: 1,
                 viewport: Some(Viewport {
@@ -0,0 +1,268 @@
+//! Import of [Aseprite](https://www.aseprite.org/) JSON sprite sheets for animated layers.
+//!
+//! Both the "Hash" and "Array" frame formats are supported. Sheets should be exported without
+//! trimming, since trimmed frames are drawn at the position of their untrimmed source.
+
+use std::time::Duration;
+
+use bevy::{asset::AssetPath, prelude::*};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{Animation, LayerData};
+
+#[derive(Debug, Error)]
+pub enum AsepriteError {
+    #[error("invalid aseprite sheet: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("aseprite sheet has no tag `{0}`")]
+    MissingTag(String),
+    #[error("aseprite sheet has no frames")]
+    NoFrames,
+    #[error("invalid image path: {0}")]
+    Path(#[from] bevy::asset::ParseAssetPathError),
+}
+
+#[derive(Deserialize)]
+struct JsonSheet {
+    frames: JsonFrames,
+    meta: JsonMeta,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonFrames {
+    Array(Vec<JsonFrame>),
+    Hash(JsonFrameHash),
+}
+
+/// Frames keyed by name, kept in file order
+struct JsonFrameHash(Vec<JsonFrame>);
+
+impl<'de> Deserialize<'de> for JsonFrameHash {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FrameHashVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FrameHashVisitor {
+            type Value = JsonFrameHash;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of frames")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some((_, frame)) = map.next_entry::<serde::de::IgnoredAny, JsonFrame>()? {
+                    frames.push(frame);
+                }
+                Ok(JsonFrameHash(frames))
+            }
+        }
+
+        deserializer.deserialize_map(FrameHashVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonFrame {
+    frame: JsonRect,
+    duration: u64,
+}
+
+#[derive(Deserialize)]
+struct JsonRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonMeta {
+    image: String,
+    size: JsonSize,
+    #[serde(default)]
+    frame_tags: Vec<AsepriteTag>,
+}
+
+#[derive(Deserialize)]
+struct JsonSize {
+    w: u32,
+    h: u32,
+}
+
+/// Playback direction of an Aseprite tag
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AsepriteDirection {
+    #[default]
+    #[serde(rename = "forward")]
+    Forward,
+    #[serde(rename = "reverse")]
+    Reverse,
+    #[serde(rename = "pingpong")]
+    PingPong,
+    #[serde(rename = "pingpong_reverse")]
+    PingPongReverse,
+}
+
+/// Named range of frames
+#[derive(Debug, Deserialize, Clone)]
+pub struct AsepriteTag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    #[serde(default)]
+    pub direction: AsepriteDirection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AsepriteFrame {
+    /// Position of the frame in the sheet image
+    pub rect: URect,
+    pub duration: Duration,
+}
+
+/// Frames and tags of an Aseprite JSON sprite sheet
+#[derive(Debug, Clone)]
+pub struct AsepriteSheet {
+    /// Asset path of the sheet image
+    pub image: String,
+    /// Size of the sheet image
+    pub size: UVec2,
+    pub frames: Vec<AsepriteFrame>,
+    pub tags: Vec<AsepriteTag>,
+}
+
+impl AsepriteSheet {
+    /// Parse a sheet from the content of its JSON file, found at the asset `path`.
+    /// The image path of the sheet is resolved relative to the JSON file.
+    pub fn from_json<'a>(json: &str, path: impl Into<AssetPath<'a>>) -> Result<Self, AsepriteError> {
+        let sheet: JsonSheet = serde_json::from_str(json)?;
+        let image = path.into().resolve_embed(&sheet.meta.image)?.to_string();
+        let frames = match sheet.frames {
+            JsonFrames::Array(frames) => frames,
+            JsonFrames::Hash(JsonFrameHash(frames)) => frames,
+        };
+        Ok(Self {
+            image,
+            size: UVec2::new(sheet.meta.size.w, sheet.meta.size.h),
+            frames: frames
+                .into_iter()
+                .map(|JsonFrame { frame, duration }| AsepriteFrame {
+                    rect: URect::new(frame.x, frame.y, frame.x + frame.w, frame.y + frame.h),
+                    duration: Duration::from_millis(duration),
+                })
+                .collect(),
+            tags: sheet.meta.frame_tags,
+        })
+    }
+
+    /// Frames played by a tag, in playback order, or every frame when no tag is given
+    pub fn tag_frames(&self, tag: Option<&str>) -> Result<Vec<AsepriteFrame>, AsepriteError> {
+        let Some(name) = tag else {
+            return Ok(self.frames.clone());
+        };
+        let tag = self
+            .tags
+            .iter()
+            .find(|tag| tag.name == name)
+            .ok_or_else(|| AsepriteError::MissingTag(name.to_string()))?;
+        let forward: Vec<AsepriteFrame> = self.frames.iter().take(tag.to + 1).skip(tag.from).copied().collect();
+        let reverse: Vec<AsepriteFrame> = forward.iter().rev().copied().collect();
+        // Ping-pong animations don't repeat the first and last frames when changing direction
+        let bounce = |first: &[AsepriteFrame], second: &[AsepriteFrame]| {
+            let inner = second.len().saturating_sub(1);
+            first.iter().chain(second.iter().take(inner).skip(1)).copied().collect()
+        };
+        Ok(match tag.direction {
+            AsepriteDirection::Forward => forward,
+            AsepriteDirection::Reverse => reverse,
+            AsepriteDirection::PingPong => bounce(&forward, &reverse),
+            AsepriteDirection::PingPongReverse => bounce(&reverse, &forward),
+        })
+    }
+}
+
+impl LayerData {
+    /// Use the frames of an Aseprite sheet, or only the frames of one of its tags, as the layer animation.
+    /// The layer `path` is set to the sheet image and its `tile_size` to the size of the first frame.
+    pub fn with_aseprite_sheet(mut self, sheet: &AsepriteSheet, tag: Option<&str>) -> Result<Self, AsepriteError> {
+        let frames = sheet.tag_frames(tag)?;
+        let first = frames.first().ok_or(AsepriteError::NoFrames)?;
+        self.path = sheet.image.clone();
+        self.tile_size = first.rect.size();
+        self.index = 0;
+        self.frames = Some(frames.iter().map(|frame| frame.rect).collect());
+        self.animation = Some(Animation::Frames(frames.iter().map(|frame| frame.duration).collect()));
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use super::AsepriteSheet;
+    use crate::{Animation, LayerData};
+
+    const SHEET: &str = r#"{
+  "frames": [
+    { "filename": "neon 0", "frame": { "x": 0, "y": 0, "w": 32, "h": 16 }, "duration": 100 },
+    { "filename": "neon 1", "frame": { "x": 32, "y": 0, "w": 32, "h": 16 }, "duration": 250 },
+    { "filename": "neon 2", "frame": { "x": 0, "y": 16, "w": 32, "h": 16 }, "duration": 50 },
+    { "filename": "neon 3", "frame": { "x": 32, "y": 16, "w": 32, "h": 16 }, "duration": 500 }
+  ],
+  "meta": {
+    "image": "neon.png",
+    "size": { "w": 64, "h": 32 },
+    "frameTags": [
+      { "name": "flicker", "from": 1, "to": 3, "direction": "pingpong" }
+    ]
+  }
+}"#;
+
+    #[test]
+    fn test_aseprite_layer() {
+        let sheet = AsepriteSheet::from_json(SHEET, "backgrounds/neon.json").unwrap();
+        assert_eq!(sheet.size, UVec2::new(64, 32));
+        assert_eq!(sheet.frames.len(), 4);
+
+        let layer = LayerData::default().with_aseprite_sheet(&sheet, None).unwrap();
+        assert_eq!(layer.path, "backgrounds/neon.png");
+        assert_eq!(layer.tile_size, UVec2::new(32, 16));
+        assert_eq!(layer.frame_count(), 4);
+        let layout = layer.create_texture_atlas_layout();
+        assert_eq!(layout.textures[3], URect::new(32, 16, 64, 32));
+
+        let layer = LayerData::default().with_aseprite_sheet(&sheet, Some("flicker")).unwrap();
+        let Some(Animation::Frames(durations)) = &layer.animation else {
+            panic!("expected frame durations");
+        };
+        let millis: Vec<u128> = durations.iter().map(Duration::as_millis).collect();
+        assert_eq!(millis, vec![250, 50, 500, 50]);
+
+        let mut update = layer.animation.as_ref().unwrap().to_sprite_update(&layer);
+        assert_eq!(update.next_index(Duration::from_millis(200)), 0);
+        assert_eq!(update.next_index(Duration::from_millis(50)), 1);
+        assert_eq!(update.next_index(Duration::from_millis(50)), 2);
+
+        assert!(LayerData::default().with_aseprite_sheet(&sheet, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_aseprite_hash_frames() {
+        let json = r#"{
+  "frames": {
+    "neon 9": { "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "duration": 100 },
+    "neon 10": { "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "duration": 200 }
+  },
+  "meta": { "image": "neon.png", "size": { "w": 16, "h": 8 } }
+}"#;
+        let sheet = AsepriteSheet::from_json(json, "neon.json").unwrap();
+        assert_eq!(sheet.frames[0].rect, URect::new(0, 0, 8, 8));
+        assert_eq!(sheet.frames[1].duration, Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,124 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+    utils::HashSet,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{CreateParallaxEvent, LayerData, ParallaxCameraComponent};
+
+/// Parallax configuration loaded from a `.parallax.ron` file
+#[derive(Asset, TypePath, Debug, Serialize, Deserialize, Clone)]
+pub struct ParallaxAsset {
+    /// Optional human readable name of the parallax
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Layers of the parallax, in the same format as [`CreateParallaxEvent::layers_data`]
+    pub layers: Vec<LayerData>,
+}
+
+impl ParallaxAsset {
+    pub fn new(layers: Vec<LayerData>) -> Self {
+        Self { name: None, layers }
+    }
+
+    /// Serialize to the `.parallax.ron` format read by [`ParallaxAssetLoader`]
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    pub fn create_event(&self, camera: Entity) -> CreateParallaxEvent {
+        CreateParallaxEvent {
+            layers_data: self.layers.clone(),
+            camera,
+        }
+    }
+}
+
+/// Loads [`ParallaxAsset`] from RON files with the `.parallax.ron` extension
+#[derive(Default)]
+pub struct ParallaxAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum ParallaxAssetLoaderError {
+    #[error("could not read parallax asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse parallax asset: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for ParallaxAssetLoader {
+    type Asset = ParallaxAsset;
+    type Settings = ();
+    type Error = ParallaxAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes::<ParallaxAsset>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["parallax.ron"]
+    }
+}
+
+/// Attach to a parallax camera to create its layers from a [`ParallaxAsset`].
+/// The layers are recreated every time the asset is modified, so enabling Bevy's
+/// `file_watcher` feature gives hot reload of the parallax.
+#[derive(Component, Debug, Clone)]
+#[require(ParallaxCameraComponent)]
+pub struct ParallaxAssetComponent {
+    pub handle: Handle<ParallaxAsset>,
+}
+
+impl ParallaxAssetComponent {
+    pub fn new(handle: Handle<ParallaxAsset>) -> Self {
+        Self { handle }
+    }
+}
+
+/// Send a [`CreateParallaxEvent`] when a camera asset is loaded, modified or replaced
+pub fn parallax_asset_system(
+    camera_query: Query<(Entity, Ref<ParallaxAssetComponent>), With<ParallaxCameraComponent>>,
+    parallax_assets: Res<Assets<ParallaxAsset>>,
+    mut asset_events: EventReader<AssetEvent<ParallaxAsset>>,
+    mut create_parallax_events: EventWriter<CreateParallaxEvent>,
+) {
+    let mut updated = HashSet::new();
+    for event in asset_events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                updated.insert(*id);
+            }
+            _ => (),
+        }
+    }
+    for (camera, parallax_asset) in camera_query.iter() {
+        if !parallax_asset.is_changed() && !updated.contains(&parallax_asset.handle.id()) {
+            continue;
+        }
+        if let Some(asset) = parallax_assets.get(&parallax_asset.handle) {
+            create_parallax_events.send(asset.create_event(camera));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParallaxAsset;
+
+    #[test]
+    fn test_parse_fishy_asset() {
+        let asset = ron::de::from_bytes::<ParallaxAsset>(include_bytes!("../assets/fishy.parallax.ron")).unwrap();
+        assert_eq!(asset.name.as_deref(), Some("Fishy"));
+        assert_eq!(asset.layers.len(), 6);
+        assert_eq!(asset.layers[0].path, "fishy_back.png");
+    }
+}
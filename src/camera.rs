@@ -51,7 +51,7 @@ impl PID {
             integral: 0.,
             clegg_integrator: self.clegg_integrator,
             integral_limit: match &self.integral_limit {
-                Some(limit) => limit.clone(),
+                Some(limit) => *limit,
                 None => Limit::new(-PI, PI),
             },
         }
@@ -66,7 +66,7 @@ impl PID {
             integral: 0.,
             clegg_integrator: self.clegg_integrator,
             integral_limit: match &self.integral_limit {
-                Some(limit) => limit.clone(),
+                Some(limit) => *limit,
                 None => Limit::default(),
             },
         }
@@ -92,7 +92,7 @@ pub enum RotationStrategy {
     },
 }
 
-fn shortest_angle(a: f32, b: f32) -> f32 {
+pub(crate) fn shortest_angle(a: f32, b: f32) -> f32 {
     let mut diff = a - b;
     if diff > PI {
         diff -= PI * 2.;
@@ -104,11 +104,19 @@ fn shortest_angle(a: f32, b: f32) -> f32 {
 }
 
 impl RotationStrategy {
+    /// Forget the accumulated PID state
+    pub fn reset(&mut self) {
+        if let Self::PID { last_error, integral, .. } = self {
+            *last_error = 0.;
+            *integral = 0.;
+        }
+    }
+
     pub fn rotation(&mut self, delta_time: f32, target: f32, current: f32) -> f32 {
         match self {
             Self::None => 0.,
             Self::Fixed => shortest_angle(target, current),
-            Self::P(kp) => shortest_angle(target, current) * kp.clone(),
+            Self::P(kp) => shortest_angle(target, current) * *kp,
             Self::PID {
                 kp,
                 ki,
@@ -119,9 +127,9 @@ impl RotationStrategy {
                 integral_limit,
             } => {
                 let error = shortest_angle(target, current);
-                let p_value = error * kp.clone();
+                let p_value = error * *kp;
                 let d_value = if delta_time != 0. {
-                    (error - last_error.clone()) / delta_time * kd.clone()
+                    (error - *last_error) / delta_time * *kd
                 } else {
                     0.
                 };
@@ -130,7 +138,7 @@ impl RotationStrategy {
                 } else {
                     *integral = integral_limit.fix(*integral + error * delta_time);
                 }
-                let i_value = integral.clone() * ki.clone();
+                let i_value = *integral * *ki;
                 *last_error = error;
                 p_value + i_value + d_value
             }
@@ -158,11 +166,19 @@ pub enum LinearAxisStrategy {
 }
 
 impl LinearAxisStrategy {
+    /// Forget the accumulated PID state
+    pub fn reset(&mut self) {
+        if let Self::PID { last_error, integral, .. } = self {
+            *last_error = 0.;
+            *integral = 0.;
+        }
+    }
+
     pub fn compute(&mut self, delta_time: f32, target: f32, current: f32) -> f32 {
         match self {
             Self::None => 0.,
             Self::Fixed => target - current,
-            Self::P(kp) => (target - current) * kp.clone(),
+            Self::P(kp) => (target - current) * *kp,
             Self::PID {
                 kp,
                 ki,
@@ -173,9 +189,9 @@ impl LinearAxisStrategy {
                 integral_limit,
             } => {
                 let error = target - current;
-                let p_value = error * kp.clone();
+                let p_value = error * *kp;
                 let d_value = if delta_time != 0. {
-                    (error - last_error.clone()) / delta_time * kd.clone()
+                    (error - *last_error) / delta_time * *kd
                 } else {
                     0.
                 };
@@ -184,7 +200,7 @@ impl LinearAxisStrategy {
                 } else {
                     *integral = integral_limit.fix(*integral + error * delta_time);
                 }
-                let i_value = integral.clone() * ki.clone();
+                let i_value = *integral * *ki;
                 *last_error = error;
                 p_value + i_value + d_value
             }
@@ -204,6 +220,11 @@ impl TranslationStrategy {
         Self { x, y }
     }
 
+    pub fn reset(&mut self) {
+        self.x.reset();
+        self.y.reset();
+    }
+
     pub fn translation(&mut self, seconds: f32, target: Vec2, current: Vec2) -> Vec2 {
         Vec2::new(
             self.x.compute(seconds, target.x, current.x),
@@ -256,6 +277,12 @@ impl CameraFollow {
         self
     }
 
+    /// Forget the accumulated state of the strategies, e.g. after a teleport
+    pub fn reset(&mut self) {
+        self.translation_strategy.reset();
+        self.rotation_strategy.reset();
+    }
+
     pub fn fixed(entity: Entity) -> Self {
         Self {
             target: entity,
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use thiserror::Error;
+
+/// Reasons a [`LayerData`](crate::LayerData) can't be used to create a layer
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ParallaxError {
+    #[error("image `{0}` could not be loaded to infer the tile size")]
+    ImageNotLoaded(String),
+    #[error("tile size {0} must not be zero")]
+    ZeroTileSize(UVec2),
+    #[error("scale {0} must be finite and not zero")]
+    InvalidScale(Vec2),
+    #[error("depth {0} must be finite")]
+    InvalidDepth(f32),
+    #[error("depth {depth} is behind a camera with focal length {focal_length}")]
+    DepthBehindCamera { depth: f32, focal_length: f32 },
+    #[error("camera has no layer with id `{0}`")]
+    LayerNotFound(String),
+    #[error("texture must have at least one column and one row, found {cols}x{rows}")]
+    EmptyGrid { cols: usize, rows: usize },
+    #[error("texture must have at least one frame")]
+    NoFrames,
+    #[error("frame index {index} is out of range for {count} frames")]
+    IndexOutOfRange { index: usize, count: usize },
+    #[error("animation fps {0} must be finite and positive")]
+    InvalidFps(f32),
+    #[error("animation duration must not be zero")]
+    ZeroDuration,
+    #[error("animation has {durations} frame durations for {count} frames")]
+    FrameDurationsMismatch { durations: usize, count: usize },
+}
+
+/// Event sent instead of creating the layers of a [`CreateParallaxEvent`](crate::CreateParallaxEvent) with invalid data
+#[derive(Event, Debug, Clone)]
+pub struct ParallaxErrorEvent {
+    pub camera: Entity,
+    /// Index of the invalid layer in the layers data
+    pub layer: usize,
+    pub error: ParallaxError,
+}
@@ -0,0 +1,108 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{LayerData, LayerDataComponent, LayerSpeed, LayerTextureComponent, ParallaxAsset, ParallaxLayers};
+
+/// Read the live configuration of the parallax layers of a camera.
+/// Speed, repeat, scroll, zoom, rotation, scale, z and color are taken from the layer entities, so changes made at runtime
+/// (e.g. through the inspector) are kept, while the other values come from the original [`LayerData`].
+/// The speed and scale of layers with a depth, and the scale of zooming layers, are derived so they are not exported.
+#[derive(SystemParam)]
+pub struct ParallaxExport<'w, 's> {
+    layers: ParallaxLayers<'w, 's>,
+    layer_query: Query<'w, 's, (&'static LayerDataComponent, &'static Transform, &'static Children)>,
+    texture_query: Query<'w, 's, &'static Sprite, With<LayerTextureComponent>>,
+}
+
+impl ParallaxExport<'_, '_> {
+    /// Current layer data of the camera layers, ordered by z
+    pub fn layers_data(&self, camera: Entity) -> Vec<LayerData> {
+        let mut layers: Vec<LayerData> = self
+            .layers
+            .iter(camera)
+            .filter_map(|(entity, layer)| self.layer_query.get(entity).ok().map(|components| (layer, components)))
+            .map(|(layer, (layer_data, transform, children))| {
+                let mut data = layer_data.data.clone();
+                // Speed and scale of layers with a depth are derived from it, and so is the scale of zooming layers
+                if data.depth.is_none() {
+                    data.speed = LayerSpeed::from_vec2(layer.speed);
+                }
+                if data.depth.is_none() && layer.zoom == 0. {
+                    data.scale = transform.scale.truncate();
+                }
+                data.repeat = layer.repeat.clone();
+                data.scroll = layer.scroll;
+                data.zoom = layer.zoom;
+                data.rotation = layer.rotation;
+                data.z = transform.translation.z;
+                if let Some(sprite) = children.iter().find_map(|child| self.texture_query.get(*child).ok()) {
+                    data.color = sprite.color;
+                }
+                data
+            })
+            .collect();
+        layers.sort_by(|a, b| a.z.total_cmp(&b.z));
+        layers
+    }
+
+    /// Current layers of the camera as a [`ParallaxAsset`]
+    pub fn asset(&self, camera: Entity) -> ParallaxAsset {
+        ParallaxAsset::new(self.layers_data(camera))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::SystemState, prelude::*};
+
+    use super::ParallaxExport;
+    use crate::{
+        LayerComponent, LayerData, LayerDataComponent, LayerRepeat, LayerSpeed, LayerTextureComponent, ParallaxAsset, ParallaxRoot,
+        RepeatStrategy,
+    };
+
+    #[test]
+    fn test_export_round_trip() {
+        let mut world = World::new();
+        let camera = world.spawn_empty().id();
+        let data = LayerData {
+            speed: LayerSpeed::Horizontal(0.5),
+            path: "clouds.png".to_string(),
+            tile_size: UVec2::new(32, 16),
+            z: 1.0,
+            ..default()
+        };
+        let texture = world.spawn((Sprite::default(), LayerTextureComponent { width: 32., height: 16. })).id();
+        let layer = world
+            .spawn((
+                LayerComponent {
+                    speed: Vec2::new(0.25, 0.75),
+                    repeat: LayerRepeat::horizontally(RepeatStrategy::MirrorHorizontally),
+                    scroll: Vec2::new(-5., 0.),
+                    ..LayerComponent::new(camera)
+                },
+                LayerDataComponent { data },
+                Transform::from_xyz(100., 0., 3.).with_scale(Vec3::splat(2.)),
+            ))
+            .add_child(texture)
+            .id();
+        world.spawn(ParallaxRoot::new(camera)).add_child(layer);
+        world.get_mut::<Sprite>(texture).unwrap().color = Color::srgb(1., 0., 0.);
+
+        let mut state: SystemState<ParallaxExport> = SystemState::new(&mut world);
+        let asset = state.get(&world).asset(camera);
+        let ron = asset.to_ron().unwrap();
+        let loaded = ron::de::from_str::<ParallaxAsset>(&ron).unwrap();
+
+        assert_eq!(loaded.layers.len(), 1);
+        let layer = &loaded.layers[0];
+        assert_eq!(layer.path, "clouds.png");
+        assert_eq!(layer.tile_size, UVec2::new(32, 16));
+        assert_eq!(layer.speed.as_vec2(), Vec2::new(0.25, 0.75));
+        assert!(matches!(layer.repeat, LayerRepeat::Horizontal(RepeatStrategy::MirrorHorizontally)));
+        assert_eq!(layer.scroll, Vec2::new(-5., 0.));
+        assert_eq!(layer.scale, Vec2::splat(2.));
+        assert_eq!(layer.z, 3.);
+        assert_eq!(layer.position, Vec2::ZERO);
+        assert_eq!(layer.color, Color::srgb(1., 0., 0.));
+    }
+}
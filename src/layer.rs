@@ -1,25 +1,43 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::SpriteFrameUpdate;
+use crate::{ParallaxError, SpriteFrameUpdate};
 #[cfg(feature = "bevy-inspector-egui")]
 use bevy_inspector_egui::prelude::*;
 
 /// Layer speed type.
 /// Layers with horizontal or vertical speed are only able to travel in one direction,
 /// while bidirectional layers can be scrolled endlessly in both directions.
-#[derive(Debug, Deserialize, Clone)]
-#[cfg_attr(feature = "bevy-inspector-egui", derive(Reflect, InspectorOptions))]
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect)]
+#[cfg_attr(feature = "bevy-inspector-egui", derive(InspectorOptions))]
 pub enum LayerSpeed {
     Horizontal(f32),
     Vertical(f32),
     Bidirectional(f32, f32),
 }
 
-#[derive(Debug, Deserialize, Clone)]
-#[cfg_attr(feature = "bevy-inspector-egui", derive(Reflect, InspectorOptions))]
+impl LayerSpeed {
+    pub fn as_vec2(&self) -> Vec2 {
+        match self {
+            Self::Horizontal(vx) => Vec2::new(*vx, 0.0),
+            Self::Vertical(vy) => Vec2::new(0.0, *vy),
+            Self::Bidirectional(vx, vy) => Vec2::new(*vx, *vy),
+        }
+    }
+
+    pub fn from_vec2(speed: Vec2) -> Self {
+        match (speed.x, speed.y) {
+            (vx, 0.0) => Self::Horizontal(vx),
+            (0.0, vy) => Self::Vertical(vy),
+            (vx, vy) => Self::Bidirectional(vx, vy),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect)]
+#[cfg_attr(feature = "bevy-inspector-egui", derive(InspectorOptions))]
 pub enum RepeatStrategy {
     Same,
     MirrorHorizontally,
@@ -48,8 +66,8 @@ impl RepeatStrategy {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
-#[cfg_attr(feature = "bevy-inspector-egui", derive(Reflect, InspectorOptions))]
+#[derive(Debug, Serialize, Deserialize, Clone, Reflect)]
+#[cfg_attr(feature = "bevy-inspector-egui", derive(InspectorOptions))]
 #[cfg_attr(feature = "bevy-inspector-egui", reflect(InspectorOptions))]
 pub enum LayerRepeat {
     Horizontal(RepeatStrategy),
@@ -71,17 +89,11 @@ impl LayerRepeat {
     }
 
     pub fn has_vertical(&self) -> bool {
-        match self {
-            Self::Horizontal(_) => false,
-            _ => true,
-        }
+        !matches!(self, Self::Horizontal(_))
     }
 
     pub fn has_horizontal(&self) -> bool {
-        match self {
-            Self::Vertical(_) => false,
-            _ => true,
-        }
+        !matches!(self, Self::Vertical(_))
     }
 
     pub fn get_strategy(&self) -> RepeatStrategy {
@@ -93,35 +105,58 @@ impl LayerRepeat {
     }
 }
 
-#[derive(Debug, Deserialize, Resource, Clone)]
-#[cfg_attr(feature = "bevy-inspector-egui", derive(Reflect, InspectorOptions))]
+#[derive(Debug, Serialize, Deserialize, Resource, Clone, Reflect)]
+#[cfg_attr(feature = "bevy-inspector-egui", derive(InspectorOptions))]
 pub enum Animation {
     FPS(f32),
     FrameDuration(Duration),
     TotalDuration(Duration),
+    /// Duration of every frame, in order
+    Frames(Vec<Duration>),
 }
 
 impl Animation {
+    pub fn validate(&self, frame_count: usize) -> Result<(), ParallaxError> {
+        match self {
+            Self::FPS(fps) if !fps.is_finite() || *fps <= 0. => Err(ParallaxError::InvalidFps(*fps)),
+            Self::FrameDuration(duration) | Self::TotalDuration(duration) if duration.is_zero() => Err(ParallaxError::ZeroDuration),
+            Self::Frames(durations) if durations.len() != frame_count => Err(ParallaxError::FrameDurationsMismatch {
+                durations: durations.len(),
+                count: frame_count,
+            }),
+            Self::Frames(durations) if durations.iter().any(Duration::is_zero) => Err(ParallaxError::ZeroDuration),
+            _ => Ok(()),
+        }
+    }
+
     pub fn to_sprite_update(&self, layer_data: &LayerData) -> SpriteFrameUpdate {
-        let total = layer_data.cols * layer_data.rows;
-        let duration = match self {
-            Self::FPS(fps) => Duration::from_secs_f32(1. / fps),
-            Self::FrameDuration(duration) => duration.clone(),
-            Self::TotalDuration(duration) => duration.div_f32(total as f32),
+        let total = layer_data.frame_count();
+        let (duration, durations) = match self {
+            Self::FPS(fps) => (Duration::from_secs_f32(1. / fps), vec![]),
+            Self::FrameDuration(duration) => (*duration, vec![]),
+            Self::TotalDuration(duration) => (duration.div_f32(total as f32), vec![]),
+            Self::Frames(durations) => {
+                let duration = durations.get(layer_data.index % durations.len().max(1));
+                (duration.copied().unwrap_or_default(), durations.clone())
+            }
         };
         SpriteFrameUpdate {
             total,
             index: layer_data.index,
             timer: Timer::new(duration, TimerMode::Repeating),
+            durations,
         }
     }
 }
 
 /// Layer initialization data
-#[derive(Debug, Deserialize, Resource, Clone)]
+#[derive(Debug, Serialize, Deserialize, Resource, Clone, Reflect)]
 #[serde(default)]
-#[cfg_attr(feature = "bevy-inspector-egui", derive(Reflect, InspectorOptions))]
+#[cfg_attr(feature = "bevy-inspector-egui", derive(InspectorOptions))]
 pub struct LayerData {
+    /// Optional identifier used to find the layer at runtime, see [`ParallaxLayers`](crate::ParallaxLayers)
+    pub id: Option<String>,
+
     /// Relative speed of layer to the camera movement.
     /// If the speed value is set to 1.0, the layer won't move in that direction.
     pub speed: LayerSpeed,
@@ -130,18 +165,35 @@ pub struct LayerData {
 
     /// Path to layer texture file
     pub path: String,
-    /// Size of a tile of the texture
+    /// Size of a tile of the texture.
+    /// When zero, it is inferred from the size of the loaded image divided by `cols` and `rows`.
     pub tile_size: UVec2,
     /// Columns in the texture file
     pub cols: usize,
     /// Rows in the texture file
     pub rows: usize,
+    /// Frame rectangles in the texture file, used instead of the `cols` and `rows` grid when set
+    pub frames: Option<Vec<URect>>,
     /// Scale of the texture
     pub scale: Vec2,
-    /// Z position of the layer
+    /// Z position of the layer, ignored when the layer has a `depth`
     pub z: f32,
     /// Default initial position of the Entity container
     pub position: Vec2,
+    /// Velocity of the layer in units per second, independent of the camera movement
+    pub scroll: Vec2,
+    /// Relative zoom of the layer to the camera zoom, changed through the orthographic projection scale.
+    /// If the zoom value is set to 1.0, the layer keeps its size on screen when the camera zooms.
+    pub zoom: f32,
+    /// Relative rotation of the layer to the camera rotation.
+    /// If the rotation value is set to 1.0, the layer rotates with the camera and stays still on screen.
+    pub rotation: f32,
+    /// Distance of the layer behind the focal plane of the camera, negative for foreground layers.
+    /// When set, the speed and the scale of the layer are derived from the camera
+    /// [`focal_length`](crate::ParallaxCameraComponent::focal_length), `speed` is ignored and `scale` is multiplied.
+    /// The draw order follows the depth too: the layer is placed at a z of `-depth`, so farther layers are drawn behind
+    /// and a layer added between two others is drawn between them, and `z` is ignored.
+    pub depth: Option<f32>,
 
     pub color: Color,
 
@@ -154,7 +206,84 @@ pub struct LayerData {
 
 impl LayerData {
     pub fn create_texture_atlas_layout(&self) -> TextureAtlasLayout {
-        TextureAtlasLayout::from_grid(self.tile_size, self.cols as u32, self.rows as u32, None, None)
+        match &self.frames {
+            Some(frames) => {
+                let size = frames.iter().fold(UVec2::ZERO, |size, frame| size.max(frame.max));
+                let mut layout = TextureAtlasLayout::new_empty(size);
+                for frame in frames {
+                    layout.add_texture(*frame);
+                }
+                layout
+            }
+            None => TextureAtlasLayout::from_grid(self.tile_size, self.cols as u32, self.rows as u32, None, None),
+        }
+    }
+
+    /// Speed and scale of the layer seen by a camera with the given focal length, when it has a depth.
+    /// Layers at the focal plane move with the world, and farther layers are smaller and follow the camera more.
+    pub fn perspective(&self, focal_length: f32) -> Option<(Vec2, Vec2)> {
+        let distance = focal_length + self.depth?;
+        if distance <= 0. {
+            return None;
+        }
+        let factor = focal_length / distance;
+        Some((Vec2::splat(1. - factor), self.scale * factor))
+    }
+
+    /// Z position of the layer, derived from its depth when it has one
+    pub fn translation_z(&self) -> f32 {
+        self.depth.map_or(self.z, |depth| -depth)
+    }
+
+    /// Check that the layer can be created without producing an empty or infinite grid of textures
+    pub fn validate(&self) -> Result<(), ParallaxError> {
+        if !self.has_auto_tile_size() && (self.tile_size.x == 0 || self.tile_size.y == 0) {
+            return Err(ParallaxError::ZeroTileSize(self.tile_size));
+        }
+        if !self.scale.is_finite() || self.scale.x == 0. || self.scale.y == 0. {
+            return Err(ParallaxError::InvalidScale(self.scale));
+        }
+        if let Some(depth) = self.depth.filter(|depth| !depth.is_finite()) {
+            return Err(ParallaxError::InvalidDepth(depth));
+        }
+        if self.frames.is_none() && (self.cols == 0 || self.rows == 0) {
+            return Err(ParallaxError::EmptyGrid {
+                cols: self.cols,
+                rows: self.rows,
+            });
+        }
+        let count = self.frame_count();
+        if count == 0 {
+            return Err(ParallaxError::NoFrames);
+        }
+        if self.index >= count {
+            return Err(ParallaxError::IndexOutOfRange { index: self.index, count });
+        }
+        match &self.animation {
+            Some(animation) => animation.validate(count),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the tile size has to be inferred
+    pub fn has_auto_tile_size(&self) -> bool {
+        self.tile_size == UVec2::ZERO
+    }
+
+    /// Infer the tile size from the size of the whole image, or from the first frame for layers with frames
+    pub fn infer_tile_size(&mut self, image_size: UVec2) {
+        self.tile_size = match &self.frames {
+            Some(frames) => frames.first().map(|frame| frame.size()).unwrap_or_default(),
+            None => image_size / UVec2::new(self.cols as u32, self.rows as u32),
+        };
+    }
+
+    /// Number of frames in the texture atlas
+    pub fn frame_count(&self) -> usize {
+        match &self.frames {
+            Some(frames) => frames.len(),
+            None => self.cols * self.rows,
+        }
     }
 
     pub fn create_sprite(&self, image: Handle<Image>, atlas: TextureAtlas) -> Sprite {
@@ -183,15 +312,21 @@ impl LayerData {
 impl Default for LayerData {
     fn default() -> Self {
         Self {
+            id: None,
             speed: LayerSpeed::Horizontal(1.0),
             repeat: LayerRepeat::Bidirectional(RepeatStrategy::Same),
             path: "".to_string(),
             tile_size: UVec2::ZERO,
             cols: 1,
             rows: 1,
+            frames: None,
             scale: Vec2::ONE,
             z: 0.0,
             position: Vec2::ZERO,
+            scroll: Vec2::ZERO,
+            zoom: 0.0,
+            rotation: 0.0,
+            depth: None,
             color: Color::WHITE,
             index: 0,
             flip: (false, false),
@@ -206,12 +341,48 @@ impl Default for LayerData {
 pub struct LayerComponent {
     /// Relative speed of layer to the camera movement
     pub speed: Vec2,
-    ///
+    /// Repeat strategy of the layer
     pub repeat: LayerRepeat,
     /// Number of rows (x) and columns (y) with the textures in the layer
     pub texture_count: Vec2,
+    /// Velocity of the layer in units per second, independent of the camera movement
+    pub scroll: Vec2,
+    /// Distance scrolled by the layer of an absolute camera, wrapped to the grid of textures on the repeating axes.
+    /// The position of a scrolling layer depends on it, save and restore it with the camera position to get the same background
+    pub scroll_offset: Vec2,
+    /// Relative zoom of the layer to the camera zoom
+    pub zoom: f32,
+    /// Relative rotation of the layer to the camera rotation
+    pub rotation: f32,
 
     pub camera: Entity,
+    /// Identifier from the layer data
+    pub id: Option<String>,
+}
+
+#[cfg(test)]
+impl LayerComponent {
+    /// Layer of the camera moving with the world, with a single texture, for the tests of the layer systems
+    pub(crate) fn new(camera: Entity) -> Self {
+        Self {
+            speed: Vec2::ZERO,
+            repeat: LayerRepeat::Bidirectional(RepeatStrategy::Same),
+            texture_count: Vec2::ONE,
+            scroll: Vec2::ZERO,
+            scroll_offset: Vec2::ZERO,
+            zoom: 0.,
+            rotation: 0.,
+            camera,
+            id: None,
+        }
+    }
+}
+
+/// Layer data the layer was created from
+#[derive(Component, Clone)]
+#[cfg_attr(feature = "bevy-inspector-egui", derive(Reflect, InspectorOptions))]
+pub struct LayerDataComponent {
+    pub data: LayerData,
 }
 
 /// Core component for layer texture
@@ -224,3 +395,130 @@ pub struct LayerTextureComponent {
     /// Height of the texture
     pub height: f32,
 }
+
+/// Logical grid coordinate of a layer texture, kept up to date when the texture wraps
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy-inspector-egui", derive(Reflect, InspectorOptions))]
+pub struct LayerTile {
+    pub coord: IVec2,
+}
+
+/// Event sent when a layer texture wraps to another tile of the grid
+#[derive(Event, Debug, Clone)]
+pub struct LayerTileWrapped {
+    pub layer: Entity,
+    pub texture: Entity,
+    pub old: IVec2,
+    pub new: IVec2,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::prelude::*;
+
+    use crate::{Animation, LayerData, ParallaxError};
+
+    #[test]
+    fn test_validate() {
+        let valid = LayerData {
+            tile_size: UVec2::new(16, 16),
+            cols: 2,
+            rows: 2,
+            index: 3,
+            animation: Some(Animation::FPS(10.)),
+            ..default()
+        };
+        assert_eq!(valid.validate(), Ok(()));
+
+        assert_eq!(LayerData::default().validate(), Ok(()));
+        assert_eq!(
+            LayerData {
+                tile_size: UVec2::new(16, 0),
+                ..valid.clone()
+            }
+            .validate(),
+            Err(ParallaxError::ZeroTileSize(UVec2::new(16, 0)))
+        );
+        assert_eq!(
+            LayerData {
+                scale: Vec2::new(1., 0.),
+                ..valid.clone()
+            }
+            .validate(),
+            Err(ParallaxError::InvalidScale(Vec2::new(1., 0.)))
+        );
+        assert_eq!(
+            LayerData { cols: 0, ..valid.clone() }.validate(),
+            Err(ParallaxError::EmptyGrid { cols: 0, rows: 2 })
+        );
+        assert_eq!(
+            LayerData { index: 4, ..valid.clone() }.validate(),
+            Err(ParallaxError::IndexOutOfRange { index: 4, count: 4 })
+        );
+        assert_eq!(
+            LayerData {
+                animation: Some(Animation::FPS(0.)),
+                ..valid.clone()
+            }
+            .validate(),
+            Err(ParallaxError::InvalidFps(0.))
+        );
+        assert_eq!(
+            LayerData {
+                animation: Some(Animation::TotalDuration(Duration::ZERO)),
+                ..valid.clone()
+            }
+            .validate(),
+            Err(ParallaxError::ZeroDuration)
+        );
+        assert_eq!(
+            LayerData {
+                animation: Some(Animation::Frames(vec![Duration::from_millis(100)])),
+                ..valid.clone()
+            }
+            .validate(),
+            Err(ParallaxError::FrameDurationsMismatch { durations: 1, count: 4 })
+        );
+    }
+
+    #[test]
+    fn test_perspective() {
+        let layer = LayerData {
+            scale: Vec2::splat(2.),
+            ..default()
+        };
+        assert_eq!(layer.perspective(10.), None);
+
+        let focal_plane = LayerData { depth: Some(0.), ..layer.clone() };
+        assert_eq!(focal_plane.perspective(10.), Some((Vec2::ZERO, Vec2::splat(2.))));
+        let far = LayerData { depth: Some(30.), ..layer.clone() };
+        assert_eq!(far.perspective(10.), Some((Vec2::splat(0.75), Vec2::splat(0.5))));
+        let foreground = LayerData { depth: Some(-5.), ..layer.clone() };
+        assert_eq!(foreground.perspective(10.), Some((Vec2::splat(-1.), Vec2::splat(4.))));
+        // Behind the camera
+        let behind = LayerData { depth: Some(-10.), ..layer };
+        assert_eq!(behind.perspective(10.), None);
+    }
+
+    #[test]
+    fn test_infer_tile_size() {
+        let mut layer = LayerData {
+            cols: 4,
+            rows: 2,
+            ..default()
+        };
+        assert!(layer.has_auto_tile_size());
+        layer.infer_tile_size(UVec2::new(128, 64));
+        assert_eq!(layer.tile_size, UVec2::new(32, 32));
+        assert!(!layer.has_auto_tile_size());
+
+        let mut layer = LayerData {
+            frames: Some(vec![URect::new(0, 0, 10, 20), URect::new(10, 0, 20, 20)]),
+            ..default()
+        };
+        layer.infer_tile_size(UVec2::ZERO);
+        assert_eq!(layer.tile_size, UVec2::new(10, 20));
+    }
+}
@@ -0,0 +1,341 @@
+//! Import of [LDtk](https://ldtk.io/) level backgrounds and layers as parallax layers.
+//!
+//! LDtk's parallax factors already follow this crate's convention, where `1.0` means the layer
+//! stays with the camera, so they are used as [`LayerSpeed`] directly. Layer images come from
+//! LDtk's "super simple export", which renders every layer of every level to a PNG file.
+//!
+//! Positions are in world pixels, with the y axis pointing up.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{CreateParallaxEvent, LayerData, LayerRepeat, LayerSpeed, ParallaxCameraComponent, RepeatStrategy};
+
+#[derive(Debug, Error)]
+pub enum LdtkError {
+    #[error("invalid ldtk project: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("layer `{0}` has no definition")]
+    MissingLayerDefinition(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonProject {
+    defs: JsonDefinitions,
+    levels: Vec<JsonLevel>,
+}
+
+#[derive(Deserialize)]
+struct JsonDefinitions {
+    layers: Vec<JsonLayerDefinition>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLayerDefinition {
+    uid: i64,
+    #[serde(default)]
+    parallax_factor_x: f32,
+    #[serde(default)]
+    parallax_factor_y: f32,
+    #[serde(default)]
+    parallax_scaling: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLevel {
+    identifier: String,
+    iid: String,
+    world_x: f32,
+    world_y: f32,
+    px_wid: u32,
+    px_hei: u32,
+    bg_rel_path: Option<String>,
+    #[serde(rename = "__bgPos")]
+    bg_pos: Option<JsonBackgroundPosition>,
+    layer_instances: Option<Vec<JsonLayerInstance>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonBackgroundPosition {
+    top_left_px: Vec2,
+    scale: Vec2,
+    crop_rect: [f32; 4],
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonLayerInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__opacity")]
+    opacity: f32,
+    #[serde(rename = "__pxTotalOffsetX")]
+    px_total_offset_x: f32,
+    #[serde(rename = "__pxTotalOffsetY")]
+    px_total_offset_y: f32,
+    layer_def_uid: i64,
+    visible: bool,
+}
+
+/// Parallax layers of a single LDtk level
+#[derive(Debug, Clone)]
+pub struct LdtkLevel {
+    /// Level identifier, e.g. `Level_0`
+    pub identifier: String,
+    /// Unique level instance identifier
+    pub iid: String,
+    pub layers: Vec<LayerData>,
+}
+
+impl LdtkLevel {
+    pub fn create_event(&self, camera: Entity) -> CreateParallaxEvent {
+        CreateParallaxEvent {
+            layers_data: self.layers.clone(),
+            camera,
+        }
+    }
+}
+
+/// Create the parallax layers of every level of an LDtk project.
+///
+/// The level background, if any, is the bottom layer. `simplified_dir` is the folder of the super simple
+/// export (usually `<project>/simplified`); when provided, each visible layer instance is added on top
+/// using its rendered `<simplified_dir>/<level>/<layer>.png` image.
+pub fn levels_from_ldtk(json: &str, simplified_dir: Option<&str>) -> Result<Vec<LdtkLevel>, LdtkError> {
+    let project: JsonProject = serde_json::from_str(json)?;
+    let definitions: HashMap<i64, &JsonLayerDefinition> = project.defs.layers.iter().map(|def| (def.uid, def)).collect();
+    project
+        .levels
+        .iter()
+        .map(|level| {
+            let level_position = Vec2::new(level.world_x, level.world_y);
+            let level_size = UVec2::new(level.px_wid, level.px_hei);
+            let mut layers = Vec::new();
+            if let (Some(path), Some(bg_pos)) = (&level.bg_rel_path, &level.bg_pos) {
+                // Only the cropped part of the image is shown, e.g. with the cover or centered modes
+                let [x, y, width, height] = bg_pos.crop_rect;
+                let size = Vec2::new(width, height);
+                let min = Vec2::new(x, y).as_uvec2();
+                layers.push(LayerData {
+                    speed: LayerSpeed::Bidirectional(0., 0.),
+                    repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
+                    path: path.clone(),
+                    tile_size: size.as_uvec2(),
+                    frames: Some(vec![URect::from_corners(min, min + size.as_uvec2())]),
+                    scale: bg_pos.scale,
+                    position: to_world(level_position + bg_pos.top_left_px + size * bg_pos.scale / 2.),
+                    ..default()
+                });
+            }
+            if let (Some(dir), Some(instances)) = (simplified_dir, &level.layer_instances) {
+                // Layer instances are listed from top to bottom
+                for instance in instances.iter().rev().filter(|instance| instance.visible) {
+                    let def = definitions
+                        .get(&instance.layer_def_uid)
+                        .ok_or_else(|| LdtkError::MissingLayerDefinition(instance.identifier.clone()))?;
+                    let factor = Vec2::new(def.parallax_factor_x, def.parallax_factor_y);
+                    let offset = Vec2::new(instance.px_total_offset_x, instance.px_total_offset_y);
+                    let scale = if def.parallax_scaling {
+                        Vec2::ONE - factor
+                    } else {
+                        Vec2::ONE
+                    };
+                    layers.push(LayerData {
+                        speed: LayerSpeed::Bidirectional(factor.x, factor.y),
+                        repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
+                        path: format!("{}/{}/{}.png", dir, level.identifier, instance.identifier),
+                        tile_size: level_size,
+                        scale,
+                        position: to_world(level_position + offset + level_size.as_vec2() / 2.),
+                        color: Color::WHITE.with_alpha(instance.opacity),
+                        ..default()
+                    });
+                }
+            }
+            for (i, layer) in layers.iter_mut().enumerate() {
+                layer.z = i as f32;
+            }
+            Ok(LdtkLevel {
+                identifier: level.identifier.clone(),
+                iid: level.iid.clone(),
+                layers,
+            })
+        })
+        .collect()
+}
+
+/// Convert an LDtk position (y down) to a world position (y up)
+fn to_world(position: Vec2) -> Vec2 {
+    Vec2::new(position.x, -position.y)
+}
+
+/// Levels of an LDtk project loaded from a `.ldtk` file
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct LdtkParallaxAsset {
+    pub levels: Vec<LdtkLevel>,
+}
+
+impl LdtkParallaxAsset {
+    /// Find a level by identifier or iid
+    pub fn level(&self, level: &str) -> Option<&LdtkLevel> {
+        self.levels
+            .iter()
+            .find(|ldtk_level| ldtk_level.identifier == level || ldtk_level.iid == level)
+    }
+}
+
+/// Loads an [`LdtkParallaxAsset`] from an LDtk project.
+/// Image paths are resolved relative to the project file.
+#[derive(Default)]
+pub struct LdtkParallaxAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum LdtkParallaxAssetLoaderError {
+    #[error("could not read ldtk project: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not read ldtk project: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error(transparent)]
+    Ldtk(#[from] LdtkError),
+    #[error("invalid image path: {0}")]
+    Path(#[from] bevy::asset::ParseAssetPathError),
+}
+
+impl AssetLoader for LdtkParallaxAssetLoader {
+    type Asset = LdtkParallaxAsset;
+    type Settings = ();
+    type Error = LdtkParallaxAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let simplified_dir = load_context
+            .path()
+            .file_stem()
+            .map(|stem| format!("{}/simplified", stem.to_string_lossy()));
+        let mut levels = levels_from_ldtk(std::str::from_utf8(&bytes)?, simplified_dir.as_deref())?;
+        for layer in levels.iter_mut().flat_map(|level| level.layers.iter_mut()) {
+            layer.path = load_context.asset_path().resolve_embed(&layer.path)?.to_string();
+        }
+        Ok(LdtkParallaxAsset { levels })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+/// Attach to a parallax camera to create its layers from a level of an LDtk project.
+/// Changing `level` recreates the layers for the new level.
+#[derive(Component, Debug, Clone)]
+#[require(ParallaxCameraComponent)]
+pub struct LdtkLevelComponent {
+    pub project: Handle<LdtkParallaxAsset>,
+    /// Identifier or iid of the current level
+    pub level: String,
+}
+
+impl LdtkLevelComponent {
+    pub fn new(project: Handle<LdtkParallaxAsset>, level: impl Into<String>) -> Self {
+        Self {
+            project,
+            level: level.into(),
+        }
+    }
+}
+
+/// Send a [`CreateParallaxEvent`] when a camera level changes or its project is loaded or modified
+pub fn ldtk_level_system(
+    camera_query: Query<(Entity, Ref<LdtkLevelComponent>), With<ParallaxCameraComponent>>,
+    ldtk_assets: Res<Assets<LdtkParallaxAsset>>,
+    mut asset_events: EventReader<AssetEvent<LdtkParallaxAsset>>,
+    mut create_parallax_events: EventWriter<CreateParallaxEvent>,
+) {
+    let mut updated = HashSet::new();
+    for event in asset_events.read() {
+        match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+                updated.insert(*id);
+            }
+            _ => (),
+        }
+    }
+    for (camera, ldtk_level) in camera_query.iter() {
+        if !ldtk_level.is_changed() && !updated.contains(&ldtk_level.project.id()) {
+            continue;
+        }
+        let level = ldtk_assets
+            .get(&ldtk_level.project)
+            .and_then(|project| project.level(&ldtk_level.level));
+        if let Some(level) = level {
+            create_parallax_events.send(level.create_event(camera));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::levels_from_ldtk;
+    use crate::LayerSpeed;
+
+    const LDTK: &str = r#"{
+  "defs": { "layers": [
+    { "uid": 1, "identifier": "Front", "parallaxFactorX": 0, "parallaxFactorY": 0, "parallaxScaling": false },
+    { "uid": 2, "identifier": "Far", "parallaxFactorX": 0.5, "parallaxFactorY": 0.25, "parallaxScaling": true }
+  ]},
+  "levels": [{
+    "identifier": "Level_0", "iid": "a1", "worldX": 100, "worldY": 50, "pxWid": 256, "pxHei": 128,
+    "bgRelPath": "sky.png",
+    "__bgPos": { "topLeftPx": [0, 0], "scale": [2, 2], "cropRect": [16, 8, 64, 32] },
+    "layerInstances": [
+      { "__identifier": "Front", "__opacity": 1, "__pxTotalOffsetX": 0, "__pxTotalOffsetY": 0, "layerDefUid": 1, "visible": true },
+      { "__identifier": "Far", "__opacity": 0.5, "__pxTotalOffsetX": 8, "__pxTotalOffsetY": 0, "layerDefUid": 2, "visible": true }
+    ]
+  }]
+}"#;
+
+    #[test]
+    fn test_levels_from_ldtk() {
+        let levels = levels_from_ldtk(LDTK, Some("world/simplified")).unwrap();
+        assert_eq!(levels.len(), 1);
+        let layers = &levels[0].layers;
+        assert_eq!(layers.len(), 3);
+
+        let background = &layers[0];
+        assert_eq!(background.path, "sky.png");
+        assert_eq!(background.tile_size, UVec2::new(64, 32));
+        assert_eq!(background.frames, Some(vec![URect::new(16, 8, 80, 40)]));
+        assert_eq!(background.scale, Vec2::splat(2.));
+        assert_eq!(background.position, Vec2::new(164., -82.));
+
+        let far = &layers[1];
+        assert_eq!(far.path, "world/simplified/Level_0/Far.png");
+        assert!(matches!(far.speed, LayerSpeed::Bidirectional(x, y) if x == 0.5 && y == 0.25));
+        assert_eq!(far.scale, Vec2::new(0.5, 0.75));
+        assert_eq!(far.position, Vec2::new(236., -114.));
+        assert_eq!(far.z, 1.);
+
+        let front = &layers[2];
+        assert_eq!(front.path, "world/simplified/Level_0/Front.png");
+        assert_eq!(front.z, 2.);
+
+        let backgrounds = levels_from_ldtk(LDTK, None).unwrap();
+        assert_eq!(backgrounds[0].layers.len(), 1);
+    }
+}
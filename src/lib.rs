@@ -1,15 +1,38 @@
-use bevy::{prelude::*};
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
+use bevy::render::camera::CameraProjection;
+use bevy::utils::HashMap;
 use bevy::window::PrimaryWindow;
 
+#[cfg(feature = "aseprite")]
+pub mod aseprite;
+pub mod asset;
 pub mod camera;
+pub mod error;
+pub mod export;
 pub mod layer;
+pub mod lookup;
+#[cfg(feature = "ldtk")]
+pub mod ldtk;
 pub mod parallax;
 pub mod sprite;
+#[cfg(feature = "tiled")]
+pub mod tiled;
 
+#[cfg(feature = "aseprite")]
+pub use aseprite::*;
+pub use asset::*;
 pub use camera::*;
+pub use error::*;
+pub use export::*;
 pub use layer::*;
+pub use lookup::*;
+#[cfg(feature = "ldtk")]
+pub use ldtk::*;
 pub use parallax::*;
 pub use sprite::*;
+#[cfg(feature = "tiled")]
+pub use tiled::*;
 
 pub struct ParallaxPlugin;
 
@@ -19,151 +42,1709 @@ impl ParallaxPlugin {
         app.register_type::<Limit>()
             .register_type::<CameraFollow>()
             .register_type::<LayerComponent>()
+            .register_type::<LayerDataComponent>()
             .register_type::<LayerTextureComponent>()
-            .register_type::<ParallaxCameraComponent>();
+            .register_type::<LayerTile>()
+            .register_type::<ParallaxCameraComponent>()
+            .register_type::<ParallaxRoot>();
     }
 
     #[cfg(not(feature = "bevy-inspector-egui"))]
     fn add_features(&self, _app: &mut App) {}
+
+    #[cfg(feature = "tiled")]
+    fn add_tiled(&self, app: &mut App) {
+        app.init_asset_loader::<TiledParallaxAssetLoader>();
+    }
+
+    #[cfg(not(feature = "tiled"))]
+    fn add_tiled(&self, _app: &mut App) {}
+
+    #[cfg(feature = "ldtk")]
+    fn add_ldtk(&self, app: &mut App) {
+        app.init_asset::<LdtkParallaxAsset>()
+            .init_asset_loader::<LdtkParallaxAssetLoader>()
+            .add_systems(PreUpdate, ldtk_level_system.before(create_parallax_system));
+    }
+
+    #[cfg(not(feature = "ldtk"))]
+    fn add_ldtk(&self, _app: &mut App) {}
 }
 
 impl Plugin for ParallaxPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<ParallaxMoveEvent>()
+            .add_event::<ParallaxTeleportEvent>()
             .add_event::<CreateParallaxEvent>()
-            .add_systems(PreUpdate, create_parallax_system)
+            .add_event::<ParallaxLayerEvent>()
+            .add_event::<DespawnParallaxEvent>()
+            .add_event::<ParallaxErrorEvent>()
+            .add_event::<LayerTileWrapped>()
+            .register_type::<ParallaxBackground>()
+            .init_asset::<ParallaxAsset>()
+            .init_asset_loader::<ParallaxAssetLoader>()
+            .add_systems(
+                PreUpdate,
+                (parallax_asset_system, create_background_system, despawn_parallax_system, create_parallax_system).chain(),
+            )
             .add_systems(Update, sprite_frame_update_system)
             .add_systems(
                 Update,
-                (camera_follow_system, move_layers_system, update_layer_textures_system)
+                (
+                    teleport_camera_system,
+                    camera_follow_system,
+                    move_layers_system,
+                    track_camera_system,
+                    scroll_layers_system,
+                    absolute_layers_system,
+                    rotate_layers_system,
+                    perspective_layers_system,
+                    resize_layers_system,
+                    update_layer_textures_system,
+                    tint_layers_system,
+                )
                     .chain()
                     .in_set(ParallaxSystems),
             );
         self.add_features(app);
+        self.add_tiled(app);
+        self.add_ldtk(app);
     }
 }
 
 #[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
 pub struct ParallaxSystems;
 
+/// What to do with the layers of a pending parallax
+enum PendingAction {
+    /// Replace every layer of the camera
+    Create,
+    /// Add the layer to the camera
+    Insert,
+    /// Replace the camera layer with the given id
+    Replace(String),
+    /// Despawn the camera layer with the given id
+    Remove(String),
+    /// Despawn every layer of the camera
+    Despawn,
+}
+
+/// Parallax waiting for the images of its auto sized layers to be loaded
+struct PendingParallax {
+    event: CreateParallaxEvent,
+    action: PendingAction,
+    images: Vec<Option<Handle<Image>>>,
+}
+
+impl PendingParallax {
+    fn new(mut event: CreateParallaxEvent, action: PendingAction, asset_server: &AssetServer) -> Self {
+        let images = event
+            .layers_data
+            .iter_mut()
+            .map(|layer| {
+                if !layer.has_auto_tile_size() {
+                    return None;
+                }
+                if layer.frames.is_some() {
+                    layer.infer_tile_size(UVec2::ZERO);
+                    return None;
+                }
+                Some(asset_server.load(&layer.path))
+            })
+            .collect();
+        Self { event, action, images }
+    }
+
+    /// Forget the pending layer with the given id, returns false when there is none
+    fn remove_layer(&mut self, id: &str) -> bool {
+        let Some(index) = self.event.layers_data.iter().position(|layer| layer.id.as_deref() == Some(id)) else {
+            return false;
+        };
+        self.event.layers_data.remove(index);
+        self.images.remove(index);
+        // The layer it was replacing is removed too
+        if let PendingAction::Replace(replaced) = &self.action {
+            self.action = PendingAction::Remove(replaced.clone());
+        }
+        true
+    }
+
+    /// Infer the tile sizes from the loaded images, returns false while some images are loading
+    fn infer_tile_sizes(&mut self, asset_server: &AssetServer, images: &Assets<Image>) -> Result<bool, Vec<ParallaxErrorEvent>> {
+        for (i, (layer, handle)) in self.event.layers_data.iter_mut().zip(&self.images).enumerate() {
+            let Some(handle) = handle else {
+                continue;
+            };
+            if let Some(image) = images.get(handle) {
+                layer.infer_tile_size(image.size());
+            } else if asset_server.load_state(handle).is_failed() {
+                return Err(vec![ParallaxErrorEvent {
+                    camera: self.event.camera,
+                    layer: i,
+                    error: ParallaxError::ImageNotLoaded(layer.path.clone()),
+                }]);
+            } else {
+                return Ok(false);
+            }
+        }
+        let errors = self.event.validate();
+        match errors.is_empty() {
+            true => Ok(true),
+            false => Err(errors),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn create_parallax_system(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
     mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
-    primary_window: Single<&Window, With<PrimaryWindow>>,
-    parallax_query: Query<(Entity, &ParallaxCameraComponent, &Camera)>,
-    layers_query: Query<(Entity, &LayerComponent)>,
+    primary_window: Option<Single<&Window, With<PrimaryWindow>>>,
+    parallax_query: Query<(&ParallaxCameraComponent, &Camera, Option<&OrthographicProjection>)>,
+    layers: ParallaxLayers,
+    layers_query: Query<(&LayerDataComponent, &Transform)>,
+    entities: &Entities,
     mut create_parallax_events: EventReader<CreateParallaxEvent>,
+    mut layer_events: EventReader<ParallaxLayerEvent>,
+    mut despawn_events: EventReader<DespawnParallaxEvent>,
+    mut error_events: EventWriter<ParallaxErrorEvent>,
+    mut pending: Local<Vec<PendingParallax>>,
 ) {
-    let mut window_size = Vec2::new(primary_window.width(), primary_window.height());
     for event in create_parallax_events.read() {
-        if let Ok((parallax_entity, parallax, camera)) = parallax_query.get(event.camera) {
-            for (entity, layer) in layers_query.iter() {
-                // If it is not my layer don't despawn
-                if layer.camera != parallax_entity {
-                    continue;
+        // Keep the current layers when the new ones are invalid
+        let errors = event.validate();
+        if !errors.is_empty() {
+            error_events.send_batch(errors);
+            continue;
+        }
+        // A new parallax replaces the one still waiting for the same camera
+        pending.retain(|parallax| parallax.event.camera != event.camera);
+        pending.push(PendingParallax::new(event.clone(), PendingAction::Create, &asset_server));
+    }
+    // Despawns win over the creations of the same frame, and are applied before the layer events
+    for event in despawn_events.read() {
+        pending.retain(|parallax| parallax.event.camera != event.camera);
+        let event = CreateParallaxEvent {
+            layers_data: vec![],
+            camera: event.camera,
+        };
+        pending.push(PendingParallax::new(event, PendingAction::Despawn, &asset_server));
+    }
+    for event in layer_events.read() {
+        let (layer, action) = match event {
+            ParallaxLayerEvent::Insert { layer, .. } => (layer, PendingAction::Insert),
+            ParallaxLayerEvent::Replace { layer, id, .. } => (layer, PendingAction::Replace(id.clone())),
+            ParallaxLayerEvent::Remove { camera, id } => {
+                // A pending layer with this id is never spawned
+                let mut found = false;
+                for parallax in pending.iter_mut().filter(|parallax| parallax.event.camera == *camera) {
+                    found |= parallax.remove_layer(id);
+                }
+                pending.retain(|parallax| !matches!(parallax.action, PendingAction::Insert) || !parallax.event.layers_data.is_empty());
+                if !found {
+                    let event = CreateParallaxEvent {
+                        layers_data: vec![],
+                        camera: *camera,
+                    };
+                    pending.push(PendingParallax::new(event, PendingAction::Remove(id.clone()), &asset_server));
+                }
+                continue;
+            }
+        };
+        let event = CreateParallaxEvent {
+            layers_data: vec![layer.clone()],
+            camera: event.camera(),
+        };
+        let errors = event.validate();
+        if !errors.is_empty() {
+            error_events.send_batch(errors);
+            continue;
+        }
+        pending.push(PendingParallax::new(event, action, &asset_server));
+    }
+    let mut waiting: Vec<PendingParallax> = Vec::new();
+    // Roots spawned, or despawned when None, by this run, not visible to the layers param until the commands are applied
+    let mut new_roots: HashMap<Entity, Option<Entity>> = HashMap::new();
+    // Layers spawned, or despawned when None, by this run, by camera and id
+    let mut new_layers: HashMap<(Entity, String), Option<Entity>> = HashMap::new();
+    for mut pending_parallax in pending.drain(..) {
+        // Events of a camera are applied in the order they were sent
+        if waiting.iter().any(|parallax| parallax.event.camera == pending_parallax.event.camera) {
+            waiting.push(pending_parallax);
+            continue;
+        }
+        match pending_parallax.infer_tile_sizes(&asset_server, &images) {
+            Ok(true) => (),
+            Ok(false) => {
+                waiting.push(pending_parallax);
+                continue;
+            }
+            Err(errors) => {
+                error_events.send_batch(errors);
+                continue;
+            }
+        }
+        // The layers are despawned even when the camera is gone
+        if let PendingAction::Despawn = pending_parallax.action {
+            let camera = pending_parallax.event.camera;
+            if let Some(root) = new_roots.get(&camera).copied().unwrap_or_else(|| layers.root(camera)) {
+                commands.entity(root).despawn_recursive();
+            }
+            new_roots.insert(camera, None);
+            forget_layers(&mut new_layers, &layers, camera);
+            continue;
+        }
+        let Ok((parallax, camera, projection)) = parallax_query.get(pending_parallax.event.camera) else {
+            // Wait for the camera components, e.g. when the background is inserted before the camera
+            if entities.contains(pending_parallax.event.camera) {
+                waiting.push(pending_parallax);
+            }
+            continue;
+        };
+        let event = pending_parallax.event;
+        let errors = event.validate_depths(parallax.focal_length);
+        if !errors.is_empty() {
+            error_events.send_batch(errors);
+            continue;
+        }
+        let window_size = view_size(camera, projection, primary_window.as_deref().copied());
+        let find_layer = |new_layers: &HashMap<(Entity, String), Option<Entity>>, id: &str| {
+            match new_layers.get(&(event.camera, id.to_string())) {
+                Some(entity) => *entity,
+                None => layers.get(event.camera, id),
+            }
+        };
+        let not_found = |id: String| ParallaxErrorEvent {
+            camera: event.camera,
+            layer: 0,
+            error: ParallaxError::LayerNotFound(id),
+        };
+        match pending_parallax.action {
+            PendingAction::Create => {
+                // Only the root of this camera is despawned, other layers are kept
+                if let Some(root) = new_roots.get(&event.camera).copied().unwrap_or_else(|| layers.root(event.camera)) {
+                    commands.entity(root).despawn_recursive();
+                }
+                forget_layers(&mut new_layers, &layers, event.camera);
+                let (root, entities) = event.create_layers(
+                    &mut commands,
+                    window_size,
+                    &asset_server,
+                    &mut texture_atlases,
+                    parallax,
+                );
+                new_roots.insert(event.camera, Some(root));
+                for (layer, entity) in event.layers_data.iter().zip(entities) {
+                    if let Some(id) = &layer.id {
+                        new_layers.insert((event.camera, id.clone()), Some(entity));
+                    }
                 }
+            }
+            PendingAction::Insert => {
+                let index = layers.iter(event.camera).count();
+                let root = camera_root(&mut commands, &mut new_roots, &layers, event.camera);
+                let layer = &event.layers_data[0];
+                let entity = layer.spawn_layer(
+                    &mut commands,
+                    event.camera,
+                    index,
+                    window_size,
+                    &asset_server,
+                    &mut texture_atlases,
+                    parallax,
+                );
+                commands.entity(root).add_child(entity);
+                if let Some(id) = &layer.id {
+                    new_layers.insert((event.camera, id.clone()), Some(entity));
+                }
+            }
+            PendingAction::Remove(id) => {
+                let Some(entity) = find_layer(&new_layers, &id) else {
+                    error_events.send(not_found(id));
+                    continue;
+                };
                 commands.entity(entity).despawn_recursive();
+                new_layers.insert((event.camera, id), None);
+            }
+            PendingAction::Despawn => (),
+            PendingAction::Replace(id) => {
+                let Some(entity) = find_layer(&new_layers, &id) else {
+                    error_events.send(not_found(id));
+                    continue;
+                };
+                let root = camera_root(&mut commands, &mut new_roots, &layers, event.camera);
+                let layer = &event.layers_data[0];
+                commands.entity(entity).despawn_recursive();
+                new_layers.insert((event.camera, id), None);
+                let new_entity = layer.spawn_layer(
+                    &mut commands,
+                    event.camera,
+                    0,
+                    window_size,
+                    &asset_server,
+                    &mut texture_atlases,
+                    parallax,
+                );
+                // Layers spawned by this run are still at their initial placement
+                if let Ok((layer_data, transform)) = layers_query.get(entity) {
+                    // Keep the distance the old layer scrolled from its initial position
+                    let offset = transform.translation.truncate() - layer_data.data.position;
+                    // and how it was zoomed and rotated around the camera, the offset includes them
+                    let scale = match layer.depth.is_some() || layer.zoom != 0. {
+                        true => transform.scale,
+                        false => layer.scale.extend(1.0),
+                    };
+                    let rotation = match layer.rotation != 0. {
+                        true => transform.rotation,
+                        false => Quat::IDENTITY,
+                    };
+                    commands.entity(new_entity).insert(Transform {
+                        translation: (layer.position + offset).extend(layer.translation_z()),
+                        rotation,
+                        scale,
+                    });
+                }
+                commands.entity(root).add_child(new_entity);
+                if let Some(id) = &layer.id {
+                    new_layers.insert((event.camera, id.clone()), Some(new_entity));
+                }
             }
-            if let Some(viewport) = &camera.viewport {
-                window_size = viewport.physical_size.as_vec2();
+        }
+    }
+    *pending = waiting;
+}
+
+/// Root of the camera layers, spawned when the camera has none
+fn camera_root(
+    commands: &mut Commands,
+    new_roots: &mut HashMap<Entity, Option<Entity>>,
+    layers: &ParallaxLayers,
+    camera: Entity,
+) -> Entity {
+    let root = new_roots.entry(camera).or_insert_with(|| layers.root(camera));
+    *root.get_or_insert_with(|| ParallaxRoot::spawn(commands, camera))
+}
+
+/// Mark every layer of the camera as despawned
+fn forget_layers(new_layers: &mut HashMap<(Entity, String), Option<Entity>>, layers: &ParallaxLayers, camera: Entity) {
+    for id in layers.iter(camera).filter_map(|(_, layer)| layer.id.clone()) {
+        new_layers.insert((camera, id), None);
+    }
+    for (_, entity) in new_layers.iter_mut().filter(|((layer_camera, _), _)| *layer_camera == camera) {
+        *entity = None;
+    }
+}
+
+/// Size of the world area visible by the camera.
+/// Its logical viewport size, or the primary window when it has no viewport, is scaled by the orthographic projection.
+/// Without a window, e.g. in headless apps rendering to an image, the area computed by bevy for the render target is used.
+fn view_size(camera: &Camera, projection: Option<&OrthographicProjection>, primary_window: Option<&Window>) -> Vec2 {
+    let size = match (&camera.viewport, primary_window) {
+        // The projection is sized by bevy from the logical size of the viewport
+        (Some(viewport), _) => camera.logical_viewport_size().unwrap_or_else(|| {
+            // Until the render target of the camera is known, the scale factor of the primary window is used
+            let scale_factor = primary_window.map_or(1., |window| window.scale_factor());
+            viewport.physical_size.as_vec2() / scale_factor
+        }),
+        (None, Some(window)) => Vec2::new(window.width(), window.height()),
+        (None, None) => {
+            return match projection {
+                Some(projection) => projection.area.size(),
+                None => camera.logical_viewport_size().unwrap_or_default(),
             }
-            event.create_layers(
-                &mut commands,
-                window_size,
-                &asset_server,
-                &mut texture_atlases,
-                parallax.render_layer,
+        }
+    };
+    match projection {
+        Some(projection) => {
+            let mut projection = projection.clone();
+            projection.update(size.x, size.y);
+            projection.area.size()
+        }
+        None => size,
+    }
+}
+
+/// Rotate the layers around their camera by the camera rotation since the last frame, scaled by their rotation factor.
+/// Layers of absolute cameras are rotated by absolute_layers_system.
+fn rotate_layers_system(
+    camera_query: Query<(Entity, &Transform, Option<&Parent>, &ParallaxCameraComponent)>,
+    parent_query: Query<&GlobalTransform>,
+    root_query: Query<&GlobalTransform, With<ParallaxRoot>>,
+    layers: ParallaxLayers,
+    mut layer_query: LayerTransformQuery,
+    mut last_angles: Local<HashMap<Entity, f32>>,
+) {
+    let mut angles = HashMap::new();
+    for (camera, camera_transform, parent, parallax) in camera_query.iter() {
+        if parallax.absolute_position {
+            continue;
+        }
+        let camera_transform = camera_world_transform(camera_transform, parent, &parent_query);
+        let angle = camera_transform.rotation.to_euler(EulerRot::XYZ).2;
+        angles.insert(camera, angle);
+        let Some(last_angle) = last_angles.get(&camera) else {
+            continue;
+        };
+        let camera_rotation = shortest_angle(angle, *last_angle);
+        if camera_rotation == 0. {
+            continue;
+        }
+        let root_transform = layers
+            .root(camera)
+            .and_then(|root| root_query.get(root).ok())
+            .copied()
+            .unwrap_or_default();
+        let camera_position = root_transform.affine().inverse().transform_point3(camera_transform.translation);
+        for (entity, layer) in layers.iter(camera) {
+            if layer.rotation == 0. {
+                continue;
+            }
+            if let Ok(mut layer_transform) = layer_query.get_mut(entity) {
+                layer_transform.rotate_around(camera_position, Quat::from_rotation_z(camera_rotation * layer.rotation));
+            }
+        }
+    }
+    *last_angles = angles;
+}
+
+/// Derive the speed and scale of the layers from their depth and the zoom of their camera
+#[allow(clippy::type_complexity)]
+fn perspective_layers_system(
+    camera_query: Query<(&ParallaxCameraComponent, &Transform, Option<&Parent>, Option<&OrthographicProjection>)>,
+    parent_query: Query<&GlobalTransform>,
+    root_query: Query<(&ParallaxRoot, &Children, &GlobalTransform)>,
+    mut layer_query: Query<(&mut LayerComponent, &LayerDataComponent, &mut Transform), Without<ParallaxCameraComponent>>,
+) {
+    for (root, children, root_transform) in root_query.iter() {
+        let Ok((parallax, camera_transform, parent, projection)) = camera_query.get(root.camera) else {
+            continue;
+        };
+        let camera_transform = camera_world_transform(camera_transform, parent, &parent_query);
+        let camera_zoom = projection.map_or(1., |projection| projection.scale);
+        let camera_position = root_transform.affine().inverse().transform_point3(camera_transform.translation).truncate();
+        for &child in children.iter() {
+            let Ok((mut layer, layer_data, mut transform)) = layer_query.get_mut(child) else {
+                continue;
+            };
+            let perspective = layer_data.data.perspective(parallax.focal_length);
+            if let Some((speed, _)) = perspective {
+                if layer.speed != speed {
+                    layer.speed = speed;
+                }
+            }
+            // Keep the scale of other layers, it may have been changed at runtime
+            if perspective.is_none() && layer.zoom == 0. {
+                continue;
+            }
+            let scale = perspective.map_or(layer_data.data.scale, |(_, scale)| scale) * camera_zoom.powf(layer.zoom);
+            let current_scale = transform.scale.truncate();
+            if current_scale == scale {
+                continue;
+            }
+            // Zoom around the camera, so the part of the layer it looks at stays in place.
+            // Layers of absolute cameras are zoomed by absolute_layers_system
+            if layer.zoom != 0. && !parallax.absolute_position {
+                let translation = camera_position + (transform.translation.truncate() - camera_position) * scale / current_scale;
+                transform.translation = translation.extend(transform.translation.z);
+            }
+            transform.scale = scale.extend(transform.scale.z);
+        }
+    }
+}
+
+/// Add or remove rows and columns of textures when the grid of a layer no longer covers the view,
+/// e.g. when the window, a camera viewport or projection, or the scale of a layer changes.
+/// The remaining textures are kept as they are, and new ones copy the sprite and animation of an existing one
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn resize_layers_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
+    primary_window: Option<Single<&Window, With<PrimaryWindow>>>,
+    camera_query: Query<(&ParallaxCameraComponent, &Camera, Option<&OrthographicProjection>, &GlobalTransform)>,
+    root_query: Query<(&ParallaxRoot, &Children)>,
+    mut layer_query: Query<(Entity, &mut LayerComponent, &LayerDataComponent, &Transform, &GlobalTransform, Option<&Children>)>,
+    texture_query: Query<(Entity, &LayerTile, &Sprite, Option<&SpriteFrameUpdate>), With<LayerTextureComponent>>,
+) {
+    for (root, children) in root_query.iter() {
+        let Ok((parallax, camera, projection, camera_transform)) = camera_query.get(root.camera) else {
+            continue;
+        };
+        let window_size = view_size(camera, projection, primary_window.as_deref().copied());
+        for &child in children.iter() {
+            let Ok((entity, mut layer, layer_data, transform, global_transform, textures)) = layer_query.get_mut(child) else {
+                continue;
+            };
+            // Size the grid with the current repeat and scale, which may have been changed at runtime
+            let tile_size = layer_data.data.tile_size.as_vec2() * transform.scale.truncate();
+            let texture_count = grid_texture_count(&layer.repeat, tile_size, window_size);
+            if texture_count == layer.texture_count {
+                continue;
+            }
+            let mut data = layer_data.data.clone();
+            data.repeat = layer.repeat.clone();
+            data.scale = transform.scale.truncate();
+            // Center the new grid on the tile under the camera to keep the current scroll position
+            let camera_position = global_transform.affine().inverse().transform_point3(camera_transform.translation());
+            let tile = (camera_position.truncate() / data.tile_size.as_vec2()).round().as_ivec2();
+            let center = IVec2::new(
+                if data.repeat.has_horizontal() { tile.x } else { 0 },
+                if data.repeat.has_vertical() { tile.y } else { 0 },
             );
+            layer.texture_count = texture_count;
+
+            let textures: Vec<_> = textures.into_iter().flatten().filter_map(|texture| texture_query.get(*texture).ok()).collect();
+            let Some(&(_, template_tile, template_sprite, template_animation)) = textures.first() else {
+                data.spawn_textures(
+                    &mut commands,
+                    entity,
+                    center,
+                    window_size,
+                    &asset_server,
+                    &mut texture_atlases,
+                    parallax.render_layer,
+                );
+                continue;
+            };
+
+            // The grid is a contiguous block of tile coordinates, find the new block that overlaps it the most
+            let min = textures.iter().fold(IVec2::MAX, |min, (_, tile, ..)| min.min(tile.coord));
+            let max = textures.iter().fold(IVec2::MIN, |max, (_, tile, ..)| max.max(tile.coord));
+            let count = texture_count.as_ivec2();
+            let x_range = resized_range(min.x, max.x, count.x, center.x);
+            let y_range = resized_range(min.y, max.y, count.y, center.y);
+
+            for (texture, tile, ..) in textures.iter() {
+                if !x_range.contains(&tile.coord.x) || !y_range.contains(&tile.coord.y) {
+                    commands.entity(*texture).despawn_recursive();
+                }
+            }
+
+            // Undo the repeat strategy of the template texture to get the sprite of the tile (0, 0)
+            let mut sprite = template_sprite.clone();
+            data.repeat.get_strategy().transform(&mut sprite, (template_tile.coord.x, template_tile.coord.y));
+            let missing = x_range
+                .flat_map(|x| y_range.clone().map(move |y| IVec2::new(x, y)))
+                .filter(|coord| coord.cmplt(min).any() || coord.cmpgt(max).any());
+            data.spawn_tiles(&mut commands, entity, &sprite, template_animation, missing, parallax.render_layer);
         }
     }
 }
 
+/// Range of `count` tile coordinates that keeps as many of the current `min..=max` tiles as possible.
+/// Grids grow on both sides, and shrink around the tile at `center`
+fn resized_range(min: i32, max: i32, count: i32, center: i32) -> std::ops::RangeInclusive<i32> {
+    let current = max - min + 1;
+    let start = if count >= current {
+        min - (count - current) / 2
+    } else {
+        (center - count / 2 + 1).clamp(min, max - count + 1)
+    };
+    start..=(start + count - 1)
+}
+
+type LayerTransformQuery<'w, 's> = Query<'w, 's, &'static mut Transform, (With<LayerComponent>, Without<ParallaxCameraComponent>)>;
+
+/// World transform of a camera, e.g. the child of a player or a physics body.
+/// The current local transform is used, the global one is only computed at the end of the frame
+fn camera_world_transform(transform: &Transform, parent: Option<&Parent>, parent_query: &Query<&GlobalTransform>) -> Transform {
+    match parent.and_then(|parent| parent_query.get(parent.get()).ok()) {
+        Some(parent_transform) => parent_transform.mul_transform(*transform).compute_transform(),
+        None => *transform,
+    }
+}
+
+/// Move the layers of the camera by the camera translation scaled by their speed
+fn move_camera_layers(layers: &ParallaxLayers, layer_query: &mut LayerTransformQuery, camera: Entity, translation: Vec3) {
+    for (entity, layer) in layers.iter(camera) {
+        let Ok(mut layer_transform) = layer_query.get_mut(entity) else {
+            continue;
+        };
+        layer_transform.translation.x += translation.x * layer.speed.x;
+        layer_transform.translation.y += translation.y * layer.speed.y;
+    }
+}
+
 /// Move camera and background layers
 fn move_layers_system(
     mut camera_query: Query<(&mut Transform, &ParallaxCameraComponent)>,
-    mut layer_query: Query<(&mut Transform, &LayerComponent), Without<ParallaxCameraComponent>>,
+    layers: ParallaxLayers,
+    mut layer_query: LayerTransformQuery,
     mut move_events: EventReader<ParallaxMoveEvent>,
 ) {
     for event in move_events.read() {
         if let Ok((mut camera_transform, parallax)) = camera_query.get_mut(event.camera) {
-            let camera_translation = camera_transform.translation.clone();
+            let camera_translation = camera_transform.translation;
             camera_transform.translation = parallax
                 .inside_limits(camera_transform.translation.truncate() + event.translation)
                 .extend(camera_transform.translation.z);
             let real_translation = camera_transform.translation - camera_translation;
             camera_transform.rotate_z(event.rotation);
-            for (mut layer_transform, layer) in layer_query.iter_mut() {
-                if layer.camera != event.camera {
-                    continue;
-                }
-                layer_transform.translation.x += real_translation.x * layer.speed.x;
-                layer_transform.translation.y += real_translation.y * layer.speed.y;
+            // Layers of tracking cameras are moved by track_camera_system, absolute ones by absolute_layers_system
+            if !parallax.track_transform && !parallax.absolute_position {
+                move_camera_layers(&layers, &mut layer_query, event.camera, real_translation);
             }
         }
     }
 }
 
-/// Update layer positions to keep the effect going indefinitely
-fn update_layer_textures_system(
-    layer_query: Query<(&LayerComponent, &Children)>,
-    mut texture_query: Query<(&GlobalTransform, &mut Transform, &LayerTextureComponent, &ViewVisibility), Without<ParallaxCameraComponent>>,
-    camera_query: Query<(Entity, &Transform, &Camera), With<ParallaxCameraComponent>>,
-    window_query: Query<&Window, With<PrimaryWindow>>,
-    mut move_events: EventReader<ParallaxMoveEvent>,
+/// Place cameras at the position of their teleport events
+fn teleport_camera_system(
+    mut camera_query: Query<(&mut Transform, &ParallaxCameraComponent, Option<&mut CameraFollow>)>,
+    layers: ParallaxLayers,
+    mut layer_query: LayerTransformQuery,
+    mut teleport_events: EventReader<ParallaxTeleportEvent>,
 ) {
-    for event in move_events.read() {
-        if !event.has_translation() {
+    for event in teleport_events.read() {
+        if let Ok((mut camera_transform, parallax, follow)) = camera_query.get_mut(event.camera) {
+            let translation = event.translation.extend(camera_transform.translation.z) - camera_transform.translation;
+            camera_transform.translation += translation;
+            if let Some(mut follow) = follow {
+                follow.reset();
+            }
+            // Layers of tracking and absolute cameras are moved by their own systems
+            if !parallax.track_transform && !parallax.absolute_position {
+                move_camera_layers(&layers, &mut layer_query, event.camera, translation);
+            }
+        }
+    }
+}
+
+/// Move the layers of cameras tracking their transform by the translation since the last frame.
+/// The translation of cameras with a parent, e.g. a player or a physics body, is tracked in world space.
+fn track_camera_system(
+    camera_query: Query<(Entity, &Transform, Option<&Parent>, &ParallaxCameraComponent)>,
+    parent_query: Query<&GlobalTransform>,
+    layers: ParallaxLayers,
+    mut layer_query: LayerTransformQuery,
+    mut last_translations: Local<HashMap<Entity, Vec3>>,
+) {
+    let mut translations = HashMap::new();
+    for (camera, camera_transform, parent, parallax) in camera_query.iter() {
+        // Layers of absolute cameras always follow the camera transform
+        if !parallax.track_transform || parallax.absolute_position {
+            continue;
+        }
+        let camera_translation = camera_world_transform(camera_transform, parent, &parent_query).translation;
+        if let Some(last_translation) = last_translations.get(&camera) {
+            let translation = camera_translation - *last_translation;
+            if translation != Vec3::ZERO {
+                move_camera_layers(&layers, &mut layer_query, camera, translation);
+            }
+        }
+        translations.insert(camera, camera_translation);
+    }
+    *last_translations = translations;
+}
+
+/// Move the layers with a scroll velocity, layers of absolute cameras are moved by absolute_layers_system
+fn scroll_layers_system(
+    time: Res<Time>,
+    camera_query: Query<(Entity, &ParallaxCameraComponent)>,
+    layers: ParallaxLayers,
+    mut layer_query: LayerTransformQuery,
+) {
+    for (camera, parallax) in camera_query.iter() {
+        if parallax.absolute_position {
             continue;
         }
-        let primary_window = window_query.get_single().unwrap();
-        let window_size = Vec2::new(primary_window.width(), primary_window.height());
-        if let Ok((camera_entity, camera_transform, camera)) = camera_query.get(event.camera) {
-            let view_size = match &camera.viewport {
-                Some(viewport) => viewport.physical_size.as_vec2(),
-                _ => window_size,
+        for (entity, layer) in layers.iter(camera) {
+            if layer.scroll == Vec2::ZERO {
+                continue;
+            }
+            if let Ok(mut layer_transform) = layer_query.get_mut(entity) {
+                layer_transform.translation += (layer.scroll * time.delta_secs()).extend(0.);
+            }
+        }
+    }
+}
+
+/// Place the layers of cameras in absolute mode from the camera position, zoom and rotation and their scroll offset,
+/// without accumulating float errors
+#[allow(clippy::type_complexity)]
+fn absolute_layers_system(
+    time: Res<Time>,
+    camera_query: Query<(&Transform, Option<&Parent>, Option<&OrthographicProjection>, &ParallaxCameraComponent)>,
+    parent_query: Query<&GlobalTransform>,
+    root_query: Query<(&ParallaxRoot, &Children, &GlobalTransform)>,
+    mut layer_query: Query<(&mut LayerComponent, &mut Transform, &LayerDataComponent), Without<ParallaxCameraComponent>>,
+) {
+    for (root, children, root_transform) in root_query.iter() {
+        let Ok((camera_transform, parent, projection, parallax)) = camera_query.get(root.camera) else {
+            continue;
+        };
+        if !parallax.absolute_position {
+            continue;
+        }
+        let camera_transform = camera_world_transform(camera_transform, parent, &parent_query);
+        let camera_zoom = projection.map_or(1., |projection| projection.scale);
+        let camera_angle = camera_transform.rotation.to_euler(EulerRot::XYZ).2;
+        let camera_position = root_transform.affine().inverse().transform_point3(camera_transform.translation).truncate();
+        for &child in children.iter() {
+            let Ok((mut layer, mut layer_transform, layer_data)) = layer_query.get_mut(child) else {
+                continue;
             };
-            for (layer, children) in layer_query.iter() {
-                if layer.camera != camera_entity {
+            let zoom = match layer.zoom != 0. {
+                true => camera_zoom.powf(layer.zoom),
+                false => 1.,
+            };
+            if layer.scroll != Vec2::ZERO {
+                // Wrap the offset every two tiles, which keeps the textures and their repeat strategy in place,
+                // so it stays small however long the layer scrolls
+                let period = 2. * layer_data.data.tile_size.as_vec2() * layer_transform.scale.truncate() / zoom;
+                let mut scroll_offset = layer.scroll_offset + layer.scroll * time.delta_secs();
+                if layer.repeat.has_horizontal() {
+                    scroll_offset.x = scroll_offset.x.rem_euclid(period.x);
+                }
+                if layer.repeat.has_vertical() {
+                    scroll_offset.y = scroll_offset.y.rem_euclid(period.y);
+                }
+                layer.scroll_offset = scroll_offset;
+            }
+            let mut translation = layer_data.data.position + camera_transform.translation.truncate() * layer.speed + layer.scroll_offset;
+            // Zoom around the camera, as perspective_layers_system does for the other cameras
+            if layer.zoom != 0. {
+                translation = camera_position + (translation - camera_position) * zoom;
+            }
+            // Rotate around the camera, as rotate_layers_system does for the other cameras
+            if layer.rotation != 0. {
+                let rotation = Quat::from_rotation_z(camera_angle * layer.rotation);
+                translation = camera_position + (rotation * (translation - camera_position).extend(0.)).truncate();
+                if layer_transform.rotation != rotation {
+                    layer_transform.rotation = rotation;
+                }
+            }
+            if layer_transform.translation.truncate() != translation {
+                layer_transform.translation = translation.extend(layer_transform.translation.z);
+            }
+        }
+    }
+}
+
+/// Color the textures of the layers with the color of their layer multiplied by the tint of their root,
+/// when the tint changes or a layer is added to a tinted root
+fn tint_layers_system(
+    root_query: Query<(Ref<ParallaxRoot>, &Children)>,
+    layer_query: Query<(Ref<LayerComponent>, &LayerDataComponent, &Children)>,
+    mut texture_query: Query<&mut Sprite, With<LayerTextureComponent>>,
+) {
+    for (root, layers) in root_query.iter() {
+        let tint_changed = root.is_changed() && !root.is_added();
+        for &layer in layers.iter() {
+            let Ok((layer, layer_data, textures)) = layer_query.get(layer) else {
+                continue;
+            };
+            let new_tinted_layer = layer.is_added() && root.tint != Color::WHITE;
+            if !tint_changed && !new_tinted_layer {
+                continue;
+            }
+            let color = LinearRgba::from_vec4(layer_data.data.color.to_linear().to_vec4() * root.tint.to_linear().to_vec4());
+            for &texture in textures.iter() {
+                if let Ok(mut sprite) = texture_query.get_mut(texture) {
+                    sprite.color = color.into();
+                }
+            }
+        }
+    }
+}
+
+/// Whether a texture of the given world size overlaps the view, checked in the rotated frame of the camera
+fn is_in_view(camera_transform: &Transform, view_size: Vec2, texture_transform: &Transform, texture_size: Vec2) -> bool {
+    let inverse_rotation = camera_transform.rotation.inverse();
+    let center = (inverse_rotation * (texture_transform.translation - camera_transform.translation)).truncate();
+    // Bounding box of the texture in the camera frame
+    let rotation = Mat2::from_mat3(Mat3::from_quat(inverse_rotation * texture_transform.rotation));
+    let texture_rect = Rect::from_center_half_size(center, rotation.abs() * texture_size / 2.0);
+    !Rect::from_center_size(Vec2::ZERO, view_size).intersect(texture_rect).is_empty()
+}
+
+/// Update layer positions to keep the effect going indefinitely.
+/// Textures are wrapped when their world bounds are outside of the area visible by the camera.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_layer_textures_system(
+    layers: ParallaxLayers,
+    root_query: Query<&GlobalTransform, With<ParallaxRoot>>,
+    layer_query: Query<(&Transform, &Children), (With<LayerComponent>, Without<LayerTextureComponent>)>,
+    mut texture_query: Query<(&mut Transform, &mut LayerTile, &LayerTextureComponent), Without<ParallaxCameraComponent>>,
+    camera_query: Query<(Entity, &Transform, Option<&Parent>, &Camera, Option<&OrthographicProjection>), With<ParallaxCameraComponent>>,
+    parent_query: Query<&GlobalTransform>,
+    primary_window: Option<Single<&Window, With<PrimaryWindow>>>,
+    mut wrapped_events: EventWriter<LayerTileWrapped>,
+) {
+    for (camera_entity, camera_transform, parent, camera, projection) in camera_query.iter() {
+        let camera_transform = &camera_world_transform(camera_transform, parent, &parent_query);
+        let view_size = view_size(camera, projection, primary_window.as_deref().copied());
+        let root_transform = layers
+            .root(camera_entity)
+            .and_then(|root| root_query.get(root).ok())
+            .copied()
+            .unwrap_or_default();
+        for (entity, layer) in layers.iter(camera_entity) {
+            let Ok((layer_transform, children)) = layer_query.get(entity) else {
+                continue;
+            };
+            // Current transforms are used instead of the global transforms computed at the end of the last frame
+            let layer_gtransform = root_transform.mul_transform(*layer_transform);
+            // Camera position in the layer space, whatever the scale and rotation of the layer
+            let camera_position = layer_gtransform.affine().inverse().transform_point3(camera_transform.translation);
+            for &child in children.iter() {
+                let Ok((mut texture_transform, mut tile, layer_texture)) = texture_query.get_mut(child) else {
+                    continue;
+                };
+                let texture_gtransform = layer_gtransform.mul_transform(*texture_transform).compute_transform();
+                let texture_size = Vec2::new(layer_texture.width, layer_texture.height) * texture_gtransform.scale.truncate();
+                // Do not move visible textures
+                if is_in_view(camera_transform, view_size, &texture_gtransform, texture_size) {
                     continue;
                 }
-                for &child in children.iter() {
-                    let (texture_gtransform, mut texture_transform, layer_texture, computed_visibility) =
-                        texture_query.get_mut(child).unwrap();
-                    // Do not move visible textures
-                    if computed_visibility.get() {
-                        continue;
-                    }
-                    let texture_gtransform = texture_gtransform.compute_transform();
-                    let texture_translation = camera_transform.translation - texture_gtransform.translation;
-                    if layer.repeat.has_horizontal() {
-                        let x_delta = layer_texture.width * layer.texture_count.x;
-                        let half_width = layer_texture.width * texture_gtransform.scale.x / 2.0;
-                        // Move not visible right texture to left side of layer when camera is moving to left
-                        if event.has_left_translation() && texture_translation.x + half_width < -view_size.x {
-                            texture_transform.translation.x -= x_delta;
-                        }
-                        // Move not visible left texture to right side of layer when camera is moving to right
-                        if event.has_right_translation() && texture_translation.x - half_width > view_size.x {
-                            texture_transform.translation.x += x_delta;
-                        }
-                    }
-                    if layer.repeat.has_vertical() {
-                        let y_delta = layer_texture.height * layer.texture_count.y;
-                        let half_height = layer_texture.height * texture_gtransform.scale.y / 2.0;
-                        // Move not visible top texture to the bottom of the layer when the camera is moving to the bottom
-                        if event.has_down_translation() && texture_translation.y + half_height < -view_size.y {
-                            texture_transform.translation.y -= y_delta;
-                        }
-                        // Move not visible bottom texture to the top of the layer when the camera is moving to the top
-                        if event.has_up_translation() && texture_translation.y - half_height > view_size.y {
-                            texture_transform.translation.y += y_delta;
-                        }
-                    }
+                // Snap the texture to the copy of the grid closest to the camera, however far the camera moved
+                let texture_translation = camera_position - texture_transform.translation;
+                let delta = Vec2::new(layer_texture.width, layer_texture.height) * layer.texture_count;
+                let mut grids = IVec2::ZERO;
+                if layer.repeat.has_horizontal() {
+                    grids.x = (texture_translation.x / delta.x).round() as i32;
+                }
+                if layer.repeat.has_vertical() {
+                    grids.y = (texture_translation.y / delta.y).round() as i32;
+                }
+                if grids == IVec2::ZERO {
+                    continue;
                 }
+                texture_transform.translation += (delta * grids.as_vec2()).extend(0.);
+                let old = tile.coord;
+                tile.coord += grids * layer.texture_count.as_ivec2();
+                wrapped_events.send(LayerTileWrapped {
+                    layer: entity,
+                    texture: child,
+                    old,
+                    new: tile.coord,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        prelude::*,
+        render::camera::{ScalingMode, Viewport},
+        window::PrimaryWindow,
+    };
+
+    use std::{
+        f32::consts::{FRAC_PI_2, FRAC_PI_4},
+        time::Duration,
+    };
+
+    use super::{
+        absolute_layers_system, create_parallax_system, is_in_view, perspective_layers_system, resize_layers_system, rotate_layers_system,
+        scroll_layers_system, teleport_camera_system, tint_layers_system, track_camera_system, update_layer_textures_system, view_size,
+    };
+    use crate::{
+        create_background_system, CameraFollow, CreateParallaxEvent, DespawnParallaxEvent, LayerComponent, LayerData, LayerDataComponent,
+        LayerRepeat, LayerTextureComponent, LayerTile, LayerTileWrapped, LinearAxisStrategy, ParallaxBackground, ParallaxCameraComponent,
+        ParallaxError, ParallaxErrorEvent, ParallaxLayerEvent, ParallaxRoot, ParallaxTeleportEvent, RepeatStrategy, PID,
+    };
+
+    /// App creating the parallax layers, without loading their images
+    fn create_parallax_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlasLayout>()
+            .add_event::<CreateParallaxEvent>()
+            .add_event::<ParallaxLayerEvent>()
+            .add_event::<DespawnParallaxEvent>()
+            .add_event::<ParallaxErrorEvent>()
+            .add_systems(Update, (create_background_system, create_parallax_system).chain());
+        app
+    }
+
+    fn layer_ids(app: &mut App) -> Vec<String> {
+        let mut query = app.world_mut().query::<&LayerComponent>();
+        query.iter(app.world()).filter_map(|layer| layer.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_background_before_camera() {
+        let mut app = create_parallax_app();
+        let layer = LayerData {
+            id: Some("clouds".to_string()),
+            tile_size: UVec2::splat(100),
+            ..default()
+        };
+        let camera = app.world_mut().spawn(ParallaxBackground::new(vec![layer])).id();
+
+        // The layers are created once the camera is ready
+        app.update();
+        assert!(layer_ids(&mut app).is_empty());
+        app.world_mut().entity_mut(camera).insert(Camera::default());
+        app.update();
+        assert_eq!(layer_ids(&mut app), vec!["clouds".to_string()]);
+    }
+
+    #[test]
+    fn test_create_layer_with_depth() {
+        let mut app = create_parallax_app();
+        let (camera, _, _) = spawn_parallax(&mut app, 1., &[]);
+        let layer = LayerData {
+            id: Some("hills".to_string()),
+            tile_size: UVec2::splat(100),
+            scale: Vec2::splat(2.),
+            depth: Some(10.),
+            z: 0.,
+            ..default()
+        };
+        app.world_mut().send_event(ParallaxLayerEvent::Insert { camera, layer: layer.clone() });
+        app.update();
+
+        // The layer has the speed and scale of its depth from the start
+        let hills = |app: &mut App, id: &str| {
+            let mut query = app.world_mut().query::<(&LayerComponent, &Transform)>();
+            let (layer, transform) = query.iter(app.world()).find(|(layer, _)| layer.id.as_deref() == Some(id)).unwrap();
+            (layer.speed, layer.texture_count, *transform)
+        };
+        let (speed, texture_count, transform) = hills(&mut app, "hills");
+        assert_eq!(speed, Vec2::splat(0.5));
+        assert_eq!(transform.scale, Vec3::ONE);
+        assert_eq!(texture_count, Vec2::new(20., 20.));
+
+        // The draw order follows the depth, whatever the z of the layers
+        let far_hills = LayerData {
+            id: Some("far_hills".to_string()),
+            depth: Some(20.),
+            z: 10.,
+            ..layer
+        };
+        app.world_mut().send_event(ParallaxLayerEvent::Insert { camera, layer: far_hills });
+        app.update();
+        assert_eq!(hills(&mut app, "hills").2.translation.z, -10.);
+        assert_eq!(hills(&mut app, "far_hills").2.translation.z, -20.);
+    }
+
+    #[test]
+    fn test_layer_behind_camera() {
+        let mut app = create_parallax_app();
+        let (camera, _, _) = spawn_parallax(&mut app, 1., &[]);
+        let layer = LayerData {
+            id: Some("fog".to_string()),
+            tile_size: UVec2::splat(100),
+            depth: Some(-10.),
+            ..default()
+        };
+        app.world_mut().send_event(ParallaxLayerEvent::Insert { camera, layer });
+        app.update();
+
+        assert!(layer_ids(&mut app).is_empty());
+        let events = app.world().resource::<Events<ParallaxErrorEvent>>();
+        let errors: Vec<&ParallaxErrorEvent> = events.iter_current_update_events().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].error,
+            ParallaxError::DepthBehindCamera {
+                depth: -10.,
+                focal_length: 10.
+            }
+        );
+    }
+
+    #[test]
+    fn test_replace_zooming_layer() {
+        let mut app = create_parallax_app();
+        app.add_systems(Update, (rotate_layers_system, perspective_layers_system).chain().after(create_parallax_system));
+        let (camera, _, _) = spawn_parallax(&mut app, 1., &[]);
+        let projection = OrthographicProjection {
+            scale: 2.,
+            ..OrthographicProjection::default_2d()
+        };
+        app.world_mut().entity_mut(camera).insert((Transform::from_xyz(100., 0., 0.), projection));
+        let layer = LayerData {
+            id: Some("sky".to_string()),
+            tile_size: UVec2::splat(100),
+            zoom: 1.,
+            rotation: 1.,
+            ..default()
+        };
+        app.world_mut().send_event(ParallaxLayerEvent::Insert { camera, layer: layer.clone() });
+        app.update();
+        app.world_mut().get_mut::<Transform>(camera).unwrap().rotate_z(FRAC_PI_2);
+        app.update();
+        let sky = |app: &mut App| {
+            let mut query = app.world_mut().query::<(&LayerComponent, &Transform)>();
+            let (_, transform) = query.iter(app.world()).find(|(layer, _)| layer.id.is_some()).unwrap();
+            *transform
+        };
+        let transform = sky(&mut app);
+        assert!(transform.translation.abs_diff_eq(Vec3::new(100., -200., 0.), 1e-3));
+
+        // The new layer keeps the zoom and rotation of the replaced one
+        let layer = LayerData {
+            path: "sky_night.png".to_string(),
+            ..layer
+        };
+        app.world_mut().send_event(ParallaxLayerEvent::Replace {
+            camera,
+            id: "sky".to_string(),
+            layer,
+        });
+        app.update();
+        app.update();
+        let replaced = sky(&mut app);
+        assert!(replaced.translation.abs_diff_eq(transform.translation, 1e-3));
+        assert_eq!(replaced.scale, transform.scale);
+        assert!(replaced.rotation.abs_diff_eq(transform.rotation, 1e-6));
+    }
+
+    #[test]
+    fn test_remove_pending_layer() {
+        let mut app = create_parallax_app();
+        let (camera, _, _) = spawn_parallax(&mut app, 1., &[]);
+        let layer = LayerData {
+            id: Some("clouds".to_string()),
+            tile_size: UVec2::splat(100),
+            ..default()
+        };
+
+        // A layer removed in the frame it is inserted is never spawned
+        app.world_mut().send_event(ParallaxLayerEvent::Insert { camera, layer: layer.clone() });
+        app.world_mut().send_event(ParallaxLayerEvent::Remove {
+            camera,
+            id: "clouds".to_string(),
+        });
+        app.update();
+        assert!(layer_ids(&mut app).is_empty());
+
+        app.world_mut().send_event(ParallaxLayerEvent::Insert { camera, layer });
+        app.update();
+        assert_eq!(layer_ids(&mut app), vec!["clouds".to_string()]);
+        app.world_mut().send_event(ParallaxLayerEvent::Remove {
+            camera,
+            id: "clouds".to_string(),
+        });
+        app.update();
+        assert!(layer_ids(&mut app).is_empty());
+    }
+
+    #[test]
+    fn test_replace_in_event_order() {
+        let mut app = create_parallax_app();
+        let (camera, _, _) = spawn_parallax(&mut app, 1., &[]);
+        let layer = |id: &str, path: &str| LayerData {
+            id: Some(id.to_string()),
+            path: path.to_string(),
+            tile_size: UVec2::splat(100),
+            ..default()
+        };
+        let replace = |id: &str, path: &str| ParallaxLayerEvent::Replace {
+            camera,
+            id: id.to_string(),
+            layer: layer(id, path),
+        };
+        let layer_paths = |app: &mut App| {
+            let mut query = app.world_mut().query::<(&LayerComponent, &LayerDataComponent)>();
+            let mut paths: Vec<String> = query
+                .iter(app.world())
+                .filter(|(layer, _)| layer.id.is_some())
+                .map(|(_, data)| data.data.path.clone())
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        // A layer replaced in the frame it is created
+        app.world_mut().send_event(CreateParallaxEvent {
+            layers_data: vec![layer("sky", "day.png")],
+            camera,
+        });
+        app.world_mut().send_event(replace("sky", "dusk.png"));
+        app.update();
+        assert_eq!(layer_paths(&mut app), vec!["dusk.png".to_string()]);
+
+        // Replaced twice in one frame
+        app.world_mut().send_event(replace("sky", "night.png"));
+        app.world_mut().send_event(replace("sky", "dawn.png"));
+        app.update();
+        assert_eq!(layer_paths(&mut app), vec!["dawn.png".to_string()]);
+
+        // A layer replaced in the frame it is inserted
+        app.world_mut().send_event(ParallaxLayerEvent::Insert {
+            camera,
+            layer: layer("sun", "sun.png"),
+        });
+        app.world_mut().send_event(replace("sun", "moon.png"));
+        app.update();
+        assert_eq!(layer_paths(&mut app), vec!["dawn.png".to_string(), "moon.png".to_string()]);
+
+        // Unknown layers are reported
+        app.world_mut().send_event(replace("stars", "stars.png"));
+        app.update();
+        let events = app.world().resource::<Events<ParallaxErrorEvent>>();
+        let errors: Vec<ParallaxError> = events.iter_current_update_events().map(|event| event.error.clone()).collect();
+        assert_eq!(errors, vec![ParallaxError::LayerNotFound("stars".to_string())]);
+    }
+
+    #[test]
+    fn test_despawn_in_event_order() {
+        let mut app = create_parallax_app();
+        let (camera, _, _) = spawn_parallax(&mut app, 1., &[]);
+        let other_camera = app.world_mut().spawn((Camera::default(), ParallaxCameraComponent::default())).id();
+        let layer = LayerData {
+            id: Some("clouds".to_string()),
+            tile_size: UVec2::splat(100),
+            ..default()
+        };
+        let roots = |app: &mut App| {
+            let mut query = app.world_mut().query::<&ParallaxRoot>();
+            query.iter(app.world()).map(|root| root.camera).collect::<Vec<_>>()
+        };
+
+        // Only the layers of the camera are despawned
+        app.world_mut().send_event(CreateParallaxEvent {
+            layers_data: vec![layer.clone()],
+            camera: other_camera,
+        });
+        app.world_mut().send_event(DespawnParallaxEvent { camera });
+        app.update();
+        assert_eq!(roots(&mut app), vec![other_camera]);
+
+        // A parallax despawned in the frame it is created is never spawned
+        app.world_mut().send_event(CreateParallaxEvent {
+            layers_data: vec![layer.clone()],
+            camera,
+        });
+        app.world_mut().send_event(DespawnParallaxEvent { camera });
+        app.update();
+        assert_eq!(roots(&mut app), vec![other_camera]);
+
+        // Same for a background inserted and removed in one frame
+        app.world_mut().entity_mut(camera).insert(ParallaxBackground::new(vec![layer.clone()]));
+        app.world_mut().entity_mut(camera).remove::<ParallaxBackground>();
+        app.update();
+        assert_eq!(roots(&mut app), vec![other_camera]);
+
+        // Layers inserted after a despawn get a new root
+        app.world_mut().send_event(DespawnParallaxEvent { camera: other_camera });
+        app.world_mut().send_event(ParallaxLayerEvent::Insert { camera: other_camera, layer });
+        app.update();
+        assert_eq!(roots(&mut app), vec![other_camera]);
+        assert_eq!(layer_ids(&mut app), vec!["clouds".to_string()]);
+    }
+
+    #[test]
+    fn test_view_size() {
+        let mut window = Window::default();
+        window.resolution.set(800., 600.);
+        let mut camera = Camera::default();
+        assert_eq!(view_size(&camera, None, Some(&window)), Vec2::new(800., 600.));
+
+        let zoomed_out = OrthographicProjection {
+            scale: 2.,
+            ..OrthographicProjection::default_2d()
+        };
+        assert_eq!(view_size(&camera, Some(&zoomed_out), Some(&window)), Vec2::new(1600., 1200.));
+
+        let fixed_vertical = OrthographicProjection {
+            scaling_mode: ScalingMode::FixedVertical { viewport_height: 300. },
+            ..OrthographicProjection::default_2d()
+        };
+        assert_eq!(view_size(&camera, Some(&fixed_vertical), Some(&window)), Vec2::new(400., 300.));
+
+        camera.viewport = Some(Viewport {
+            physical_size: UVec2::new(400, 300),
+            ..default()
+        });
+        assert_eq!(view_size(&camera, Some(&zoomed_out), Some(&window)), Vec2::new(800., 600.));
+
+        // Viewports are in physical pixels, halved on a HiDPI screen
+        let mut hidpi_window = Window::default();
+        hidpi_window.resolution.set_scale_factor(2.);
+        hidpi_window.resolution.set(800., 600.);
+        assert_eq!(view_size(&camera, Some(&zoomed_out), Some(&hidpi_window)), Vec2::new(400., 300.));
+    }
+
+    #[test]
+    fn test_is_in_view_rotated() {
+        let view_size = Vec2::new(800., 600.);
+        let texture_size = Vec2::splat(100.);
+        // Outside of the unrotated view, but in the corner of the view rotated by 45 degrees
+        let texture = Transform::from_xyz(0., 400., 0.);
+        assert!(!is_in_view(&Transform::IDENTITY, view_size, &texture, texture_size));
+        let rotated = Transform::from_rotation(Quat::from_rotation_z(FRAC_PI_4));
+        assert!(is_in_view(&rotated, view_size, &texture, texture_size));
+
+        // Inside of the unrotated view, but outside of the rotated one
+        let texture = Transform::from_xyz(420., 320., 0.);
+        assert!(is_in_view(&Transform::IDENTITY, view_size, &texture, texture_size));
+        assert!(!is_in_view(&rotated, view_size, &texture, texture_size));
+    }
+
+    #[test]
+    fn test_teleport() {
+        let mut app = App::new();
+        app.add_event::<ParallaxTeleportEvent>()
+            .add_event::<LayerTileWrapped>()
+            .add_systems(Update, (teleport_camera_system, update_layer_textures_system).chain());
+        let positions: Vec<f32> = (-9..=10).map(|x| x as f32 * 100.).collect();
+        let (camera, layer, textures) = spawn_parallax(&mut app, 20., &positions);
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().speed = Vec2::new(0.5, 0.5);
+        let mut follow = CameraFollow::pid(Entity::PLACEHOLDER, &PID::default());
+        follow.translation_strategy.x.compute(0.1, 100., 0.);
+        app.world_mut().entity_mut(camera).insert(follow);
+
+        app.world_mut().send_event(ParallaxTeleportEvent {
+            translation: Vec2::new(50_000., 0.),
+            camera,
+        });
+        app.update();
+        assert_eq!(app.world().get::<Transform>(camera).unwrap().translation, Vec3::new(50_000., 0., 0.));
+        assert_eq!(app.world().get::<Transform>(layer).unwrap().translation, Vec3::new(25_000., 0., 0.));
+        let follow = app.world().get::<CameraFollow>(camera).unwrap();
+        assert!(matches!(
+            follow.translation_strategy.x,
+            LinearAxisStrategy::PID {
+                last_error: 0.,
+                integral: 0.,
+                ..
             }
+        ));
+        // The textures surround the camera in the same frame
+        for texture in &textures {
+            let x = app.world().get::<Transform>(*texture).unwrap().translation.x;
+            assert!((25_000. + x - 50_000.).abs() <= 1000.);
+        }
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<LayerTileWrapped>()
+            .add_systems(Update, (scroll_layers_system, update_layer_textures_system).chain());
+        let positions: Vec<f32> = (-9..=10).map(|x| x as f32 * 100.).collect();
+        let (camera, layer, textures) = spawn_parallax(&mut app, 20., &positions);
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().scroll = Vec2::new(-1000., 0.);
+
+        // The layer scrolls while the camera is still, and its textures keep covering the view
+        for _ in 0..3 {
+            app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs(10));
+            app.update();
+        }
+        assert_eq!(app.world().get::<Transform>(camera).unwrap().translation, Vec3::ZERO);
+        assert_eq!(app.world().get::<Transform>(layer).unwrap().translation, Vec3::new(-30_000., 0., 0.));
+        for texture in &textures {
+            let x = app.world().get::<Transform>(*texture).unwrap().translation.x;
+            assert!((x - 30_000.).abs() <= 1000.);
+        }
+    }
+
+    #[test]
+    fn test_zoom() {
+        let mut app = App::new();
+        app.add_systems(Update, perspective_layers_system);
+        let (camera, layer, _) = spawn_parallax(&mut app, 1., &[]);
+        let projection = OrthographicProjection {
+            scale: 2.,
+            ..OrthographicProjection::default_2d()
+        };
+        app.world_mut().entity_mut(camera).insert((Transform::from_xyz(100., 0., 0.), projection));
+        app.world_mut().entity_mut(layer).insert(LayerDataComponent { data: LayerData::default() });
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().zoom = 1.;
+
+        // The layer keeps its size on screen when the camera zooms out, around the camera position
+        app.update();
+        let transform = app.world().get::<Transform>(layer).unwrap();
+        assert_eq!(transform.scale, Vec3::new(2., 2., 1.));
+        assert_eq!(transform.translation, Vec3::new(-100., 0., 0.));
+
+        app.world_mut().get_mut::<OrthographicProjection>(camera).unwrap().scale = 1.;
+        app.update();
+        let transform = app.world().get::<Transform>(layer).unwrap();
+        assert_eq!(transform.scale, Vec3::ONE);
+        assert_eq!(transform.translation, Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_absolute_zoom() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_systems(Update, (absolute_layers_system, perspective_layers_system).chain());
+        let (camera, layer, _) = spawn_parallax(&mut app, 1., &[]);
+        let projection = OrthographicProjection {
+            scale: 2.,
+            ..OrthographicProjection::default_2d()
+        };
+        let parallax = ParallaxCameraComponent::default().with_absolute_position();
+        app.world_mut().entity_mut(camera).insert((Transform::from_xyz(100., 0., 0.), projection, parallax));
+        app.world_mut().entity_mut(layer).insert(LayerDataComponent { data: LayerData::default() });
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().zoom = 1.;
+
+        // The layer stays anchored on the camera frame after frame
+        for _ in 0..3 {
+            app.update();
+            let transform = app.world().get::<Transform>(layer).unwrap();
+            assert_eq!(transform.scale, Vec3::new(2., 2., 1.));
+            assert_eq!(transform.translation, Vec3::new(-100., 0., 0.));
+        }
+
+        app.world_mut().get_mut::<OrthographicProjection>(camera).unwrap().scale = 1.;
+        app.update();
+        let transform = app.world().get::<Transform>(layer).unwrap();
+        assert_eq!(transform.scale, Vec3::ONE);
+        assert_eq!(transform.translation, Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut app = App::new();
+        app.add_systems(Update, rotate_layers_system);
+        let (camera, layer, _) = spawn_parallax(&mut app, 1., &[]);
+        app.world_mut().entity_mut(camera).insert(Transform::from_xyz(100., 0., 0.));
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().rotation = 0.5;
+        app.update();
+
+        // The layer rotates around the camera by half the camera rotation
+        app.world_mut().get_mut::<Transform>(camera).unwrap().rotate_z(FRAC_PI_2);
+        app.update();
+        let transform = app.world().get::<Transform>(layer).unwrap();
+        assert!(transform.rotation.abs_diff_eq(Quat::from_rotation_z(FRAC_PI_4), 1e-6));
+        let expected = Vec3::new(100., 0., 0.) + Quat::from_rotation_z(FRAC_PI_4) * Vec3::new(-100., 0., 0.);
+        assert!(transform.translation.abs_diff_eq(expected, 1e-4));
+    }
+
+    #[test]
+    fn test_absolute_rotation() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_systems(Update, (absolute_layers_system, rotate_layers_system).chain());
+        let (camera, layer, _) = spawn_parallax(&mut app, 1., &[]);
+        let parallax = ParallaxCameraComponent::default().with_absolute_position();
+        app.world_mut().entity_mut(camera).insert((Transform::from_xyz(100., 0., 0.), parallax));
+        app.world_mut().entity_mut(layer).insert(LayerDataComponent { data: LayerData::default() });
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().rotation = 0.5;
+        app.update();
+
+        // The layer keeps orbiting the camera frame after frame
+        app.world_mut().get_mut::<Transform>(camera).unwrap().rotate_z(FRAC_PI_2);
+        for _ in 0..3 {
+            app.update();
+            let transform = app.world().get::<Transform>(layer).unwrap();
+            assert!(transform.rotation.abs_diff_eq(Quat::from_rotation_z(FRAC_PI_4), 1e-6));
+            let expected = Vec3::new(100., 0., 0.) + Quat::from_rotation_z(FRAC_PI_4) * Vec3::new(-100., 0., 0.);
+            assert!(transform.translation.abs_diff_eq(expected, 1e-4));
+        }
+    }
+
+    #[test]
+    fn test_wrap_rotated_layer() {
+        let mut app = App::new();
+        app.add_event::<LayerTileWrapped>()
+            .add_systems(Update, update_layer_textures_system);
+        let positions: Vec<f32> = (-9..=10).map(|x| x as f32 * 100.).collect();
+        let (camera, layer, textures) = spawn_parallax(&mut app, 20., &positions);
+        app.world_mut().get_mut::<Transform>(layer).unwrap().rotate_z(FRAC_PI_2);
+
+        // The horizontal axis of the layer is the vertical axis of the world
+        move_camera(&mut app, camera, Vec2::new(0., 10_000.));
+        for texture in &textures {
+            let x = app.world().get::<Transform>(*texture).unwrap().translation.x;
+            assert!((x - 10_000.).abs() <= 1000.);
         }
     }
+
+    /// Spawn a window, a camera and a horizontal layer with 100x100 textures at the given positions
+    fn spawn_parallax(app: &mut App, texture_count: f32, positions: &[f32]) -> (Entity, Entity, Vec<Entity>) {
+        let world = app.world_mut();
+        let mut window = Window::default();
+        window.resolution.set(800., 600.);
+        world.spawn((window, PrimaryWindow));
+        let camera = world.spawn((Camera::default(), ParallaxCameraComponent::default())).id();
+        let textures: Vec<Entity> = positions
+            .iter()
+            .map(|x| {
+                world
+                    .spawn((
+                        Transform::from_xyz(*x, 0., 0.),
+                        LayerTextureComponent { width: 100., height: 100. },
+                        LayerTile {
+                            coord: IVec2::new((x / 100.) as i32, 0),
+                        },
+                    ))
+                    .id()
+            })
+            .collect();
+        let layer = world
+            .spawn((
+                LayerComponent {
+                    repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
+                    texture_count: Vec2::new(texture_count, 1.),
+                    ..LayerComponent::new(camera)
+                },
+                Transform::default(),
+            ))
+            .add_children(&textures)
+            .id();
+        world.spawn(ParallaxRoot::new(camera)).add_child(layer);
+        (camera, layer, textures)
+    }
+
+    fn move_camera(app: &mut App, camera: Entity, translation: Vec2) {
+        app.world_mut().get_mut::<Transform>(camera).unwrap().translation += translation.extend(0.);
+        app.update();
+    }
+
+    fn texture_positions(app: &App, textures: &[Entity]) -> Vec<f32> {
+        textures
+            .iter()
+            .map(|texture| app.world().get::<Transform>(*texture).unwrap().translation.x)
+            .collect()
+    }
+
+    #[test]
+    fn test_resize_keeps_textures() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlasLayout>()
+            .add_systems(Update, resize_layers_system);
+        let positions: Vec<f32> = (-9..=10).map(|x| x as f32 * 100.).collect();
+        let (_, layer, textures) = spawn_parallax(&mut app, 20., &positions);
+        let data = LayerData {
+            repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
+            tile_size: UVec2::new(100, 100),
+            ..default()
+        };
+        app.world_mut().entity_mut(layer).insert(LayerDataComponent { data });
+        for texture in textures.iter() {
+            app.world_mut().entity_mut(*texture).insert(Sprite::default());
+        }
+        let tinted = textures[9];
+        app.world_mut().get_mut::<Sprite>(tinted).unwrap().color = Color::srgb(1., 0., 0.);
+
+        let grid = |app: &mut App, scale: f32| {
+            app.world_mut().get_mut::<Transform>(layer).unwrap().scale = Vec3::splat(scale);
+            app.update();
+            let mut coords: Vec<i32> = app
+                .world_mut()
+                .query::<&LayerTile>()
+                .iter(app.world())
+                .map(|tile| tile.coord.x)
+                .collect();
+            coords.sort();
+            coords
+        };
+
+        // Twice bigger tiles need half the textures, around the camera
+        assert_eq!(grid(&mut app, 2.), (-4..=5).collect::<Vec<_>>());
+        assert_eq!(app.world().get::<LayerComponent>(layer).unwrap().texture_count, Vec2::new(10., 1.));
+        assert!(app.world().get_entity(textures[0]).is_err());
+        // Growing back keeps the remaining textures and adds the missing ones
+        assert_eq!(grid(&mut app, 1.), (-9..=10).collect::<Vec<_>>());
+        assert_eq!(app.world().get::<Sprite>(tinted).unwrap().color, Color::srgb(1., 0., 0.));
+        assert_eq!(app.world().get::<LayerTile>(tinted).unwrap().coord, IVec2::ZERO);
+        assert_eq!(app.world().get::<Transform>(tinted).unwrap().translation.x, 0.);
+    }
+
+    #[test]
+    fn test_tint() {
+        let mut app = App::new();
+        app.add_systems(Update, tint_layers_system);
+        let (camera, layer, textures) = spawn_parallax(&mut app, 2., &[0., 100.]);
+        let data = LayerData {
+            color: Color::srgb(1., 0.5, 0.),
+            ..default()
+        };
+        app.world_mut().entity_mut(layer).insert(LayerDataComponent { data });
+        for texture in textures.iter() {
+            app.world_mut().entity_mut(*texture).insert(Sprite::default());
+        }
+        app.update();
+        assert_eq!(app.world().get::<Sprite>(textures[0]).unwrap().color, Color::WHITE);
+
+        // The tint of the root is multiplied with the color of every layer
+        let mut query = app.world_mut().query::<&mut ParallaxRoot>();
+        query.iter_mut(app.world_mut()).find(|root| root.camera == camera).unwrap().tint = Color::linear_rgb(0.5, 0.5, 1.);
+        app.update();
+        for texture in textures {
+            let color = app.world().get::<Sprite>(texture).unwrap().color.to_linear();
+            let expected = Color::srgb(1., 0.5, 0.).to_linear() * 0.5;
+            assert!(color.to_vec4().abs_diff_eq(expected.with_alpha(1.).to_vec4(), 1e-6), "{color:?}");
+        }
+    }
+
+    #[test]
+    fn test_wrap_without_renderer() {
+        let mut app = App::new();
+        app.add_event::<LayerTileWrapped>()
+            .add_systems(Update, update_layer_textures_system);
+        let (camera, _, textures) = spawn_parallax(&mut app, 4., &[-100., 0., 100., 1000.]);
+
+        // Textures left behind the camera view wrap around it, the visible one stays in place
+        move_camera(&mut app, camera, Vec2::new(1000., 0.));
+        assert_eq!(texture_positions(&app, &textures), vec![1100., 1200., 900., 1000.]);
+    }
+
+    #[test]
+    fn test_wrap_headless() {
+        let mut app = App::new();
+        app.add_event::<LayerTileWrapped>()
+            .add_systems(Update, update_layer_textures_system);
+        let (camera, _, textures) = spawn_parallax(&mut app, 4., &[-100., 0., 100., 1000.]);
+        let window = app.world_mut().query_filtered::<Entity, With<PrimaryWindow>>().single(app.world());
+        app.world_mut().despawn(window);
+        // Area computed by bevy for the render target of the camera
+        let projection = OrthographicProjection {
+            area: Rect::from_center_size(Vec2::ZERO, Vec2::new(800., 600.)),
+            ..OrthographicProjection::default_2d()
+        };
+        app.world_mut().entity_mut(camera).insert(projection);
+
+        move_camera(&mut app, camera, Vec2::new(1000., 0.));
+        assert_eq!(texture_positions(&app, &textures), vec![1100., 1200., 900., 1000.]);
+    }
+
+    #[test]
+    fn test_wrap_after_large_jumps() {
+        let mut app = App::new();
+        app.add_event::<LayerTileWrapped>()
+            .add_systems(Update, update_layer_textures_system);
+        let positions: Vec<f32> = (-9..=10).map(|x| x as f32 * 100.).collect();
+        let (camera, _, textures) = spawn_parallax(&mut app, 20., &positions);
+
+        for jump in [12_345., -250_000., 3_000., -7_777.] {
+            move_camera(&mut app, camera, Vec2::new(jump, 0.));
+            let camera_x = app.world().get::<Transform>(camera).unwrap().translation.x;
+            let mut positions = texture_positions(&app, &textures);
+            positions.sort_by(f32::total_cmp);
+            // The whole grid surrounds the camera without gaps
+            assert!(positions.iter().all(|x| (x - camera_x).abs() <= 1000.), "{camera_x}: {positions:?}");
+            assert!(positions.windows(2).all(|pair| pair[1] - pair[0] == 100.), "{camera_x}: {positions:?}");
+        }
+    }
+
+    #[test]
+    fn test_tile_wrapped() {
+        let mut app = App::new();
+        app.add_event::<LayerTileWrapped>()
+            .add_systems(Update, update_layer_textures_system);
+        let (camera, _, textures) = spawn_parallax(&mut app, 4., &[-100., 0., 100., 200.]);
+
+        move_camera(&mut app, camera, Vec2::new(1000., 0.));
+        for texture in &textures {
+            let x = app.world().get::<Transform>(*texture).unwrap().translation.x;
+            assert_eq!(app.world().get::<LayerTile>(*texture).unwrap().coord, IVec2::new((x / 100.) as i32, 0));
+        }
+        let events = app.world().resource::<Events<LayerTileWrapped>>();
+        let wrapped: Vec<&LayerTileWrapped> = events.iter_current_update_events().collect();
+        assert_eq!(wrapped.len(), 4);
+        assert_eq!(wrapped[0].texture, textures[0]);
+        assert_eq!((wrapped[0].old, wrapped[0].new), (IVec2::new(-1, 0), IVec2::new(11, 0)));
+    }
+
+    #[test]
+    fn test_track_transform() {
+        let mut app = App::new();
+        app.add_systems(Update, track_camera_system);
+        let (camera, layer, _) = spawn_parallax(&mut app, 1., &[]);
+        app.world_mut().entity_mut(camera).insert(ParallaxCameraComponent::default().with_track_transform());
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().speed = Vec2::new(0.5, 1.);
+        app.update();
+
+        // Camera transforms written by other systems move the layers
+        move_camera(&mut app, camera, Vec2::new(100., 40.));
+        move_camera(&mut app, camera, Vec2::new(-20., 0.));
+        assert_eq!(app.world().get::<Transform>(layer).unwrap().translation, Vec3::new(40., 40., 0.));
+
+        // Unless the camera stops tracking its transform
+        app.world_mut().get_mut::<ParallaxCameraComponent>(camera).unwrap().track_transform = false;
+        move_camera(&mut app, camera, Vec2::new(100., 0.));
+        assert_eq!(app.world().get::<Transform>(layer).unwrap().translation, Vec3::new(40., 40., 0.));
+    }
+
+    #[test]
+    fn test_track_parented_camera() {
+        let mut app = App::new();
+        app.add_event::<LayerTileWrapped>()
+            .add_systems(Update, (track_camera_system, update_layer_textures_system).chain());
+        let positions: Vec<f32> = (-9..=10).map(|x| x as f32 * 100.).collect();
+        let (camera, layer, textures) = spawn_parallax(&mut app, 20., &positions);
+        app.world_mut().entity_mut(camera).insert(ParallaxCameraComponent::default().with_track_transform());
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().speed = Vec2::new(0.5, 1.);
+        let player = app.world_mut().spawn(Transform::default()).add_child(camera).id();
+        app.update();
+
+        // The camera follows its parent without changing its own transform
+        *app.world_mut().get_mut::<GlobalTransform>(player).unwrap() = GlobalTransform::from_xyz(100., 40., 0.);
+        app.update();
+        assert_eq!(app.world().get::<Transform>(layer).unwrap().translation, Vec3::new(50., 40., 0.));
+
+        // The textures surround the camera in the layer, which moved half as far as the player
+        *app.world_mut().get_mut::<GlobalTransform>(player).unwrap() = GlobalTransform::from_xyz(10_000., 40., 0.);
+        app.update();
+        assert_eq!(app.world().get::<Transform>(layer).unwrap().translation.x, 5000.);
+        let mut positions = texture_positions(&app, &textures);
+        positions.sort_by(f32::total_cmp);
+        assert!(positions.iter().all(|x| (x - 5000.).abs() <= 1000.), "{positions:?}");
+        assert!(positions.windows(2).all(|pair| pair[1] - pair[0] == 100.), "{positions:?}");
+    }
+
+    #[test]
+    fn test_absolute_position() {
+        let mut app = App::new();
+        app.init_resource::<Time>().add_systems(Update, absolute_layers_system);
+        let (camera, layer, _) = spawn_parallax(&mut app, 1., &[]);
+        app.world_mut().entity_mut(camera).insert(ParallaxCameraComponent::default().with_absolute_position());
+        let data = LayerData {
+            position: Vec2::new(10., 20.),
+            ..default()
+        };
+        app.world_mut()
+            .entity_mut(layer)
+            .insert((LayerDataComponent { data }, Transform::from_xyz(10., 20., 2.)));
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().speed = Vec2::new(0.3, 0.7);
+
+        // Many small moves end exactly where a single move does
+        for _ in 0..1000 {
+            move_camera(&mut app, camera, Vec2::new(0.1, -0.3));
+        }
+        let translation = app.world().get::<Transform>(camera).unwrap().translation;
+        let expected = Vec3::new(10. + translation.x * 0.3, 20. + translation.y * 0.7, 2.);
+        assert_eq!(app.world().get::<Transform>(layer).unwrap().translation, expected);
+
+        // Coming back to the start restores the initial background
+        app.world_mut().get_mut::<Transform>(camera).unwrap().translation = Vec3::ZERO;
+        app.update();
+        assert_eq!(app.world().get::<Transform>(layer).unwrap().translation, Vec3::new(10., 20., 2.));
+    }
+
+    #[test]
+    fn test_absolute_scroll() {
+        let mut app = App::new();
+        app.init_resource::<Time>().add_systems(Update, absolute_layers_system);
+        let (camera, layer, _) = spawn_parallax(&mut app, 1., &[]);
+        app.world_mut().entity_mut(camera).insert(ParallaxCameraComponent::default().with_absolute_position());
+        let data = LayerData {
+            tile_size: UVec2::splat(100),
+            ..default()
+        };
+        app.world_mut().entity_mut(layer).insert(LayerDataComponent { data });
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().scroll = Vec2::new(10., 5.);
+        let advance = |app: &mut App, seconds: u64| {
+            app.world_mut().resource_mut::<Time>().advance_by(Duration::from_secs(seconds));
+            app.update();
+            let offset = app.world().get::<LayerComponent>(layer).unwrap().scroll_offset;
+            (offset, app.world().get::<Transform>(layer).unwrap().translation.truncate())
+        };
+
+        // The offset of the repeating axis wraps every two tiles, the other one keeps growing
+        assert_eq!(advance(&mut app, 25), (Vec2::new(50., 125.), Vec2::new(50., 125.)));
+
+        // A saved offset restores the same background
+        app.world_mut().get_mut::<LayerComponent>(layer).unwrap().scroll_offset = Vec2::new(30., 0.);
+        assert_eq!(advance(&mut app, 0), (Vec2::new(30., 0.), Vec2::new(30., 0.)));
+    }
 }
 
 #[cfg(doctest)]
@@ -0,0 +1,83 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{LayerComponent, ParallaxRoot};
+
+/// Find the parallax layers of a camera, e.g. to tint, hide or change the textures of a layer at runtime
+#[derive(SystemParam)]
+pub struct ParallaxLayers<'w, 's> {
+    root_query: Query<'w, 's, (Entity, &'static ParallaxRoot, Option<&'static Children>)>,
+    layer_query: Query<'w, 's, (Entity, &'static LayerComponent, Option<&'static Children>)>,
+}
+
+impl ParallaxLayers<'_, '_> {
+    /// Root entity of the camera layers
+    pub fn root(&self, camera: Entity) -> Option<Entity> {
+        self.root_query
+            .iter()
+            .find(|(_, root, _)| root.camera == camera)
+            .map(|(entity, ..)| entity)
+    }
+
+    /// Layer entities of the camera with their component
+    pub fn iter(&self, camera: Entity) -> impl Iterator<Item = (Entity, &LayerComponent)> {
+        self.root_query
+            .iter()
+            .filter(move |(_, root, _)| root.camera == camera)
+            .filter_map(|(_, _, children)| children)
+            .flat_map(|children| children.iter())
+            .filter_map(|child| self.layer_query.get(*child).ok())
+            .map(|(entity, layer, _)| (entity, layer))
+    }
+
+    /// Layer entity of the camera with the given identifier
+    pub fn get(&self, camera: Entity, id: &str) -> Option<Entity> {
+        self.iter(camera)
+            .find(|(_, layer)| layer.id.as_deref() == Some(id))
+            .map(|(entity, _)| entity)
+    }
+
+    /// Texture entities of the layer of the camera with the given identifier
+    pub fn textures(&self, camera: Entity, id: &str) -> impl Iterator<Item = Entity> + '_ {
+        self.get(camera, id)
+            .and_then(|entity| self.layer_query.get(entity).ok())
+            .and_then(|(_, _, children)| children)
+            .into_iter()
+            .flat_map(|children| children.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::SystemState, prelude::*};
+
+    use super::ParallaxLayers;
+    use crate::{LayerComponent, ParallaxRoot};
+
+    fn layer(camera: Entity, id: Option<&str>) -> LayerComponent {
+        LayerComponent {
+            id: id.map(str::to_string),
+            ..LayerComponent::new(camera)
+        }
+    }
+
+    #[test]
+    fn test_find_layer() {
+        let mut world = World::new();
+        let camera = world.spawn_empty().id();
+        let other_camera = world.spawn_empty().id();
+        let texture = world.spawn_empty().id();
+        let clouds = world.spawn(layer(camera, Some("clouds"))).add_child(texture).id();
+        let stars = world.spawn(layer(camera, None)).id();
+        let other_clouds = world.spawn(layer(other_camera, Some("clouds"))).id();
+        let root = world.spawn(ParallaxRoot::new(camera)).add_children(&[clouds, stars]).id();
+        world.spawn(ParallaxRoot::new(other_camera)).add_child(other_clouds);
+
+        let mut state: SystemState<ParallaxLayers> = SystemState::new(&mut world);
+        let layers = state.get(&world);
+        assert_eq!(layers.root(camera), Some(root));
+        assert_eq!(layers.get(camera, "clouds"), Some(clouds));
+        assert_eq!(layers.get(camera, "stars"), None);
+        assert_eq!(layers.iter(camera).count(), 2);
+        assert_eq!(layers.textures(camera, "clouds").collect::<Vec<_>>(), vec![texture]);
+    }
+}
@@ -1,115 +1,341 @@
-use crate::layer;
-use bevy::{prelude::*, render::view::RenderLayers};
+use crate::{layer, ParallaxError, ParallaxErrorEvent, SpriteFrameUpdate};
+use bevy::{
+    ecs::{component::ComponentId, world::DeferredWorld},
+    prelude::*,
+    render::view::RenderLayers,
+};
 
 #[cfg(feature = "bevy-inspector-egui")]
 use bevy_inspector_egui::prelude::*;
 
 /// Event to setup and create parallax
-#[derive(Event, Debug)]
+#[derive(Event, Debug, Clone)]
 pub struct CreateParallaxEvent {
     pub layers_data: Vec<layer::LayerData>,
     pub camera: Entity,
 }
 
 impl CreateParallaxEvent {
-    /// Create layers from layer data
+    /// Validate every layer data, returning an error event for each invalid layer
+    pub fn validate(&self) -> Vec<ParallaxErrorEvent> {
+        self.layers_data
+            .iter()
+            .enumerate()
+            .filter_map(|(i, layer)| {
+                layer.validate().err().map(|error| ParallaxErrorEvent {
+                    camera: self.camera,
+                    layer: i,
+                    error,
+                })
+            })
+            .collect()
+    }
+
+    /// Check that the layers with a depth are in front of a camera with the given focal length
+    pub fn validate_depths(&self, focal_length: f32) -> Vec<ParallaxErrorEvent> {
+        self.layers_data
+            .iter()
+            .enumerate()
+            .filter_map(|(i, layer)| {
+                let depth = layer.depth?;
+                layer.perspective(focal_length).is_none().then_some(ParallaxErrorEvent {
+                    camera: self.camera,
+                    layer: i,
+                    error: ParallaxError::DepthBehindCamera { depth, focal_length },
+                })
+            })
+            .collect()
+    }
+
+    /// Create layers from layer data under a new [`ParallaxRoot`],
+    /// returning the root entity and the layer entities in the order of the layers data
     pub fn create_layers(
         &self,
         commands: &mut Commands,
         window_size: Vec2,
         asset_server: &AssetServer,
         texture_atlases: &mut Assets<TextureAtlasLayout>,
+        parallax: &ParallaxCameraComponent,
+    ) -> (Entity, Vec<Entity>) {
+        let root = ParallaxRoot::spawn(commands, self.camera);
+        // Spawn new layers using layer_data
+        let layers: Vec<Entity> = self
+            .layers_data
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| layer.spawn_layer(commands, self.camera, i, window_size, asset_server, texture_atlases, parallax))
+            .collect();
+        commands.entity(root).add_children(&layers);
+        (root, layers)
+    }
+}
+
+/// Number of textures of a grid with tiles of the given world size needed to cover a view of the given size
+pub(crate) fn grid_texture_count(repeat: &layer::LayerRepeat, tile_size: Vec2, window_size: Vec2) -> Vec2 {
+    let max_index = grid_max_index(repeat, tile_size, window_size);
+    Vec2::new(f32::max(2.0 * max_index.x as f32, 1.), f32::max(2.0 * max_index.y as f32, 1.))
+}
+
+fn grid_max_index(repeat: &layer::LayerRepeat, tile_size: Vec2, window_size: Vec2) -> IVec2 {
+    // The diagonal keeps the view covered whatever the rotation of the camera
+    let max_length = window_size.length();
+
+    let y_max_index = match repeat.has_vertical() {
+        true => f32::ceil(max_length / tile_size.y) as i32,
+        false => 0,
+    };
+
+    let x_max_index = match repeat.has_horizontal() {
+        true => f32::ceil(max_length / tile_size.x) as i32,
+        false => 0,
+    };
+    IVec2::new(x_max_index, y_max_index)
+}
+
+/// Parent of every layer of a camera.
+/// Its transform, visibility and tint apply to all the layers, and despawning it despawns the whole parallax.
+#[derive(Component, Debug, Clone)]
+#[require(Transform, Visibility)]
+#[cfg_attr(feature = "bevy-inspector-egui", derive(Reflect, InspectorOptions))]
+pub struct ParallaxRoot {
+    pub camera: Entity,
+    /// Color multiplied with the color of every layer.
+    /// Changing it sets the color of all the textures, replacing the colors set on single textures
+    pub tint: Color,
+}
+
+impl ParallaxRoot {
+    pub fn new(camera: Entity) -> Self {
+        Self {
+            camera,
+            tint: Color::WHITE,
+        }
+    }
+
+    /// Spawn an empty root for the camera layers
+    pub fn spawn(commands: &mut Commands, camera: Entity) -> Entity {
+        commands.spawn((Name::new("Parallax Root"), Self::new(camera))).id()
+    }
+}
+
+impl layer::LayerData {
+    /// Spawn the layer entity with its grid of textures, returning the layer entity
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn_layer(
+        &self,
+        commands: &mut Commands,
+        camera: Entity,
+        index: usize,
+        window_size: Vec2,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlasLayout>,
+        parallax: &ParallaxCameraComponent,
+    ) -> Entity {
+        let render_layer = parallax.render_layer;
+        // Layers with a depth move and are sized as seen by the camera from the first frame
+        let (speed, scale) = self.perspective(parallax.focal_length).unwrap_or((self.speed.as_vec2(), self.scale));
+        let mut data = self.clone();
+        data.scale = scale;
+
+        // Spawn parallax layer entity
+        let mut entity_commands = commands.spawn_empty();
+        entity_commands
+            .insert(Name::new(match &self.id {
+                Some(id) => format!("Parallax Layer ({})", id),
+                None => format!("Parallax Layer ({})", index),
+            }))
+            .insert(RenderLayers::from_layers(&[render_layer.into()]))
+            .insert((Transform {
+                translation: self.position.extend(self.translation_z()),
+                scale: scale.extend(1.0),
+                ..default()
+            }, Visibility::Visible));
+
+        // Add layer component to entity
+        entity_commands
+            .insert(layer::LayerComponent {
+                speed,
+                repeat: self.repeat.clone(),
+                texture_count: data.texture_count(window_size),
+                scroll: self.scroll,
+                scroll_offset: Vec2::ZERO,
+                zoom: self.zoom,
+                rotation: self.rotation,
+                camera,
+                id: self.id.clone(),
+            })
+            .insert(layer::LayerDataComponent { data: self.clone() })
+            .insert(RenderLayers::from_layers(&[render_layer.into()]));
+        let entity = entity_commands.id();
+        data.spawn_textures(commands, entity, IVec2::ZERO, window_size, asset_server, texture_atlases, render_layer);
+        entity
+    }
+
+    /// Number of textures of the grid needed to cover a view of the given size
+    pub fn texture_count(&self, window_size: Vec2) -> Vec2 {
+        grid_texture_count(&self.repeat, self.tile_size.as_vec2() * self.scale, window_size)
+    }
+
+    /// Spawn the grid of textures of the layer around the tile at `center`
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn_textures(
+        &self,
+        commands: &mut Commands,
+        layer: Entity,
+        center: IVec2,
+        window_size: Vec2,
+        asset_server: &AssetServer,
+        texture_atlases: &mut Assets<TextureAtlasLayout>,
         render_layer: u8,
     ) {
-        // Spawn new layers using layer_data
-        for (i, layer) in self.layers_data.iter().enumerate() {
-            let texture: Handle<Image> = asset_server.load(&layer.path);
-            let texture_atlas = layer.create_texture_atlas_layout();
-            let texture_atlas_handle = texture_atlases.add(texture_atlas);
-
-            let sprite_bundle = layer.create_sprite(texture, TextureAtlas::from(texture_atlas_handle));
-
-            // Spawn a grid of textures, so that they convincingly wrap around the screen when scrolling.
-            // For no repeat layers, only spawn a single row or column in direction of their movement.
-
-            // In every row of the grid, our goal is to have a central texture and at least two that surround it,
-            // plus as much as it would take to fill the rest of the window space in both directions.
-            // The grid should have a pair number so the mirror repeat can work correctly
-            // Same logic applies to vertical placement.
-
-            let max_length = window_size.length();
-
-            let y_max_index = match layer.repeat.has_vertical() {
-                true => f32::ceil(max_length / (layer.tile_size.y as f32 * layer.scale.y)) as i32,
-                false => 0,
-            };
-
-            let x_max_index = match layer.repeat.has_horizontal() {
-                true => f32::ceil(max_length / (layer.tile_size.x as f32 * layer.scale.x)) as i32,
-                false => 0,
-            };
-
-            let texture_count = Vec2::new(f32::max(2.0 * x_max_index as f32, 1.), f32::max(2.0 * y_max_index as f32, 1.));
-
-            let x_range = if layer.repeat.has_horizontal() {
-                (-x_max_index + 1)..=x_max_index
-            } else {
-                0..=0
-            };
-            let y_range = if layer.repeat.has_vertical() {
-                (-y_max_index + 1)..=y_max_index
-            } else {
-                0..=0
-            };
-
-            // Spawn parallax layer entity
-            let mut entity_commands = commands.spawn_empty();
-            entity_commands
-                .insert(Name::new(format!("Parallax Layer ({})", i)))
-                .insert(RenderLayers::from_layers(&[render_layer.into()]))
-                .insert((Transform {
-                    translation: Vec3::new(layer.position.x, layer.position.y, layer.z),
-                    scale: layer.scale.extend(1.0),
-                    ..default()
-                }, Visibility::Visible))
-                .with_children(|parent| {
-                    for x in x_range {
-                        for y in y_range.clone() {
-                            let repeat_strategy = layer.repeat.get_strategy();
-                            let mut adjusted_sprite = sprite_bundle.clone();
-                            repeat_strategy.transform(&mut adjusted_sprite, (x, y));
-
-                            let mut transform = Transform::default();
-                            transform.translation.x = layer.tile_size.x as f32 * x as f32;
-                            transform.translation.y = layer.tile_size.y as f32 * y as f32;
-                            let mut child_commands = parent.spawn((adjusted_sprite, transform));
-                            child_commands
-                                .insert(RenderLayers::from_layers(&[render_layer.into()]))
-                                .insert(layer.crate_layer_texture());
-                            if let Some(animation_bundle) = layer.create_animation_bundle() {
-                                child_commands.insert(animation_bundle);
-                            }
-                        }
-                    }
-                });
-
-            // Add layer component to entity
-            entity_commands
-                .insert(layer::LayerComponent {
-                    speed: match layer.speed {
-                        layer::LayerSpeed::Horizontal(vx) => Vec2::new(vx, 0.0),
-                        layer::LayerSpeed::Vertical(vy) => Vec2::new(0.0, vy),
-                        layer::LayerSpeed::Bidirectional(vx, vy) => Vec2::new(vx, vy),
-                    },
-                    repeat: layer.repeat.clone(),
-                    texture_count,
-                    camera: self.camera,
-                })
-                .insert(RenderLayers::from_layers(&[render_layer.into()]));
+        let texture: Handle<Image> = asset_server.load(&self.path);
+        let texture_atlas = self.create_texture_atlas_layout();
+        let texture_atlas_handle = texture_atlases.add(texture_atlas);
+
+        let sprite_bundle = self.create_sprite(texture, TextureAtlas::from(texture_atlas_handle));
+
+        // Spawn a grid of textures, so that they convincingly wrap around the screen when scrolling.
+        // For no repeat layers, only spawn a single row or column in direction of their movement.
+
+        // In every row of the grid, our goal is to have a central texture and at least two that surround it,
+        // plus as much as it would take to fill the rest of the window space in both directions.
+        // The grid should have a pair number so the mirror repeat can work correctly
+        // Same logic applies to vertical placement.
+
+        let max_index = grid_max_index(&self.repeat, self.tile_size.as_vec2() * self.scale, window_size);
+
+        let x_range = if self.repeat.has_horizontal() {
+            (center.x - max_index.x + 1)..=(center.x + max_index.x)
+        } else {
+            0..=0
+        };
+        let y_range = if self.repeat.has_vertical() {
+            (center.y - max_index.y + 1)..=(center.y + max_index.y)
+        } else {
+            0..=0
+        };
+
+        let coords = x_range.flat_map(|x| y_range.clone().map(move |y| IVec2::new(x, y)));
+        let animation = self.animation.as_ref().map(|animation| animation.to_sprite_update(self));
+        self.spawn_tiles(commands, layer, &sprite_bundle, animation.as_ref(), coords, render_layer);
+    }
+
+    /// Spawn the textures of the layer at the given tile coordinates, from the sprite and animation of the tile (0, 0)
+    pub fn spawn_tiles(
+        &self,
+        commands: &mut Commands,
+        layer: Entity,
+        sprite: &Sprite,
+        animation: Option<&SpriteFrameUpdate>,
+        coords: impl IntoIterator<Item = IVec2>,
+        render_layer: u8,
+    ) {
+        commands.entity(layer).with_children(|parent| {
+            for coord in coords {
+                let repeat_strategy = self.repeat.get_strategy();
+                let mut adjusted_sprite = sprite.clone();
+                repeat_strategy.transform(&mut adjusted_sprite, (coord.x, coord.y));
+
+                let mut transform = Transform::default();
+                transform.translation.x = self.tile_size.x as f32 * coord.x as f32;
+                transform.translation.y = self.tile_size.y as f32 * coord.y as f32;
+                let mut child_commands = parent.spawn((adjusted_sprite, transform));
+                child_commands
+                    .insert(RenderLayers::from_layers(&[render_layer.into()]))
+                    .insert(self.crate_layer_texture())
+                    .insert(layer::LayerTile { coord });
+                if let Some(animation) = animation {
+                    child_commands.insert(animation.clone());
+                }
+            }
+        });
+    }
+}
+
+/// Event to change a single layer of a camera without recreating the others.
+/// Layers are identified by their [`LayerData::id`](layer::LayerData::id).
+#[derive(Event, Debug, Clone)]
+pub enum ParallaxLayerEvent {
+    /// Add a new layer
+    Insert { camera: Entity, layer: layer::LayerData },
+    /// Despawn a layer
+    Remove { camera: Entity, id: String },
+    /// Recreate a layer from new data, keeping its scroll offset
+    Replace {
+        camera: Entity,
+        id: String,
+        layer: layer::LayerData,
+    },
+}
+
+impl ParallaxLayerEvent {
+    pub fn camera(&self) -> Entity {
+        match self {
+            Self::Insert { camera, .. } | Self::Remove { camera, .. } | Self::Replace { camera, .. } => *camera,
+        }
+    }
+}
+
+/// Event to despawn every layer of a camera.
+/// It is applied after the [`CreateParallaxEvent`] and before the [`ParallaxLayerEvent`] sent in the same frame.
+#[derive(Event, Debug, Clone)]
+pub struct DespawnParallaxEvent {
+    pub camera: Entity,
+}
+
+/// Despawn the layers of cameras that were despawned or lost their [`ParallaxCameraComponent`].
+/// Layers of a [`DespawnParallaxEvent`] are despawned in order with the other parallax events
+pub fn despawn_parallax_system(
+    mut commands: Commands,
+    root_query: Query<(Entity, &ParallaxRoot)>,
+    mut removed_cameras: RemovedComponents<ParallaxCameraComponent>,
+) {
+    let cameras: Vec<Entity> = removed_cameras.read().collect();
+    if cameras.is_empty() {
+        return;
+    }
+    for (entity, root) in root_query.iter() {
+        if cameras.contains(&root.camera) {
+            commands.entity(entity).despawn_recursive();
         }
     }
 }
 
+/// Attach to a camera to create its parallax layers.
+/// The layers are recreated every time the component is inserted or changed, and despawned when it is removed.
+/// It can be stored in scenes.
+#[derive(Component, Reflect, Debug, Clone, Default)]
+#[reflect(Component, Default)]
+#[require(ParallaxCameraComponent)]
+#[component(on_remove = despawn_background_layers)]
+pub struct ParallaxBackground {
+    pub layers: Vec<layer::LayerData>,
+}
+
+impl ParallaxBackground {
+    pub fn new(layers: Vec<layer::LayerData>) -> Self {
+        Self { layers }
+    }
+}
+
+/// Send a [`CreateParallaxEvent`] when a camera background is inserted or changed
+pub fn create_background_system(
+    background_query: Query<(Entity, &ParallaxBackground), Changed<ParallaxBackground>>,
+    mut create_parallax_events: EventWriter<CreateParallaxEvent>,
+) {
+    for (camera, background) in background_query.iter() {
+        create_parallax_events.send(CreateParallaxEvent {
+            layers_data: background.layers.clone(),
+            camera,
+        });
+    }
+}
+
+/// Send a [`DespawnParallaxEvent`] when a camera background is removed
+fn despawn_background_layers(mut world: DeferredWorld, camera: Entity, _: ComponentId) {
+    world.send_event(DespawnParallaxEvent { camera });
+}
+
 /// Event used to update parallax
 #[derive(Event, Debug)]
 pub struct ParallaxMoveEvent {
@@ -144,12 +370,29 @@ impl ParallaxMoveEvent {
     }
 }
 
+/// Event to place a camera at an absolute position in a single step.
+/// Its layers and textures are moved accordingly, limits are not applied and the [`CameraFollow`](crate::CameraFollow) state is reset.
+#[derive(Event, Debug, Clone)]
+pub struct ParallaxTeleportEvent {
+    pub translation: Vec2,
+    pub camera: Entity,
+}
+
 /// Attach to a single camera to be used with parallax
 #[derive(Component)]
 #[cfg_attr(feature = "bevy-inspector-egui", derive(Reflect, InspectorOptions))]
 pub struct ParallaxCameraComponent {
     pub render_layer: u8,
     pub limits: Vec2Limit,
+    /// Move the layers with every change of the camera `Transform` instead of [`ParallaxMoveEvent`],
+    /// for cameras moved by other systems
+    pub track_transform: bool,
+    /// Place the layers from the camera position instead of accumulating its movements,
+    /// so the background only depends on where the camera is
+    pub absolute_position: bool,
+    /// Distance from the camera to the plane where layers with a depth of zero move with the world,
+    /// see [`LayerData::depth`](layer::LayerData::depth)
+    pub focal_length: f32,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -228,6 +471,18 @@ impl ParallaxCameraComponent {
             ..default()
         }
     }
+
+    /// Move the layers with every change of the camera `Transform`
+    pub fn with_track_transform(mut self) -> Self {
+        self.track_transform = true;
+        self
+    }
+
+    /// Place the layers at their position plus the camera position scaled by their speed
+    pub fn with_absolute_position(mut self) -> Self {
+        self.absolute_position = true;
+        self
+    }
 }
 
 impl Default for ParallaxCameraComponent {
@@ -235,15 +490,24 @@ impl Default for ParallaxCameraComponent {
         Self {
             render_layer: 0,
             limits: default(),
+            track_transform: false,
+            absolute_position: false,
+            focal_length: 10.,
         }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
+    use std::any::TypeId;
+
     use bevy::prelude::*;
 
-    use crate::ParallaxMoveEvent;
+    use crate::{
+        create_background_system, despawn_parallax_system, CreateParallaxEvent, DespawnParallaxEvent, LayerComponent, LayerData,
+        LayerRepeat, ParallaxBackground, ParallaxCameraComponent, ParallaxMoveEvent, ParallaxRoot, RepeatStrategy,
+    };
 
     #[test]
     fn test_check() {
@@ -257,7 +521,7 @@ mod tests {
         let no_movement = ParallaxMoveEvent {
             translation: Vec2::ZERO,
             rotation: 0.,
-            camera: camera,
+            camera,
         };
         assert_eq!(no_movement.has_translation(), false);
         assert_eq!(no_movement.has_up_translation(), false);
@@ -268,7 +532,7 @@ mod tests {
         let up = ParallaxMoveEvent {
             translation: Vec2::new(0., 1.),
             rotation: 0.,
-            camera: camera,
+            camera,
         };
         assert_eq!(up.has_translation(), true);
         assert_eq!(up.has_up_translation(), true);
@@ -279,7 +543,7 @@ mod tests {
         let down = ParallaxMoveEvent {
             translation: Vec2::new(0., -1.),
             rotation: 0.,
-            camera: camera,
+            camera,
         };
         assert_eq!(down.has_translation(), true);
         assert_eq!(down.has_up_translation(), false);
@@ -290,7 +554,7 @@ mod tests {
         let left = ParallaxMoveEvent {
             translation: Vec2::new(-1., 0.),
             rotation: 0.,
-            camera: camera,
+            camera,
         };
         assert_eq!(left.has_translation(), true);
         assert_eq!(left.has_up_translation(), false);
@@ -301,7 +565,7 @@ mod tests {
         let right = ParallaxMoveEvent {
             translation: Vec2::new(1., 0.),
             rotation: 0.,
-            camera: camera,
+            camera,
         };
         assert_eq!(right.has_translation(), true);
         assert_eq!(right.has_up_translation(), false);
@@ -312,7 +576,7 @@ mod tests {
         let left_down = ParallaxMoveEvent {
             translation: Vec2::new(-1., -1.),
             rotation: 0.,
-            camera: camera,
+            camera,
         };
         assert_eq!(left_down.has_translation(), true);
         assert_eq!(left_down.has_up_translation(), false);
@@ -323,7 +587,7 @@ mod tests {
         let up_right = ParallaxMoveEvent {
             translation: Vec2::new(1., 1.),
             rotation: 0.,
-            camera: camera,
+            camera,
         };
         assert_eq!(up_right.has_translation(), true);
         assert_eq!(up_right.has_up_translation(), true);
@@ -331,4 +595,108 @@ mod tests {
         assert_eq!(up_right.has_left_translation(), false);
         assert_eq!(up_right.has_right_translation(), true);
     }
+
+    #[test]
+    fn test_parallax_background() {
+        let mut app = App::new();
+        app.add_event::<CreateParallaxEvent>()
+            .add_event::<DespawnParallaxEvent>()
+            .add_systems(Update, create_background_system);
+        let camera = app.world_mut().spawn(ParallaxBackground::new(vec![LayerData::default()])).id();
+        assert!(app.world().get::<ParallaxCameraComponent>(camera).is_some());
+        let sent_layers = |app: &mut App| {
+            app.update();
+            let events = app.world().resource::<Events<CreateParallaxEvent>>();
+            let sent: Vec<(Entity, usize)> = events
+                .iter_current_update_events()
+                .map(|event| (event.camera, event.layers_data.len()))
+                .collect();
+            sent
+        };
+        assert_eq!(sent_layers(&mut app), vec![(camera, 1)]);
+        assert_eq!(sent_layers(&mut app), vec![]);
+
+        // Changing the background recreates the layers
+        app.world_mut().get_mut::<ParallaxBackground>(camera).unwrap().layers.push(LayerData::default());
+        assert_eq!(sent_layers(&mut app), vec![(camera, 2)]);
+
+        // So does inserting a new one
+        app.world_mut().entity_mut(camera).insert(ParallaxBackground::default());
+        assert_eq!(sent_layers(&mut app), vec![(camera, 0)]);
+
+        // Removing it despawns them
+        app.world_mut().entity_mut(camera).remove::<ParallaxBackground>();
+        let events = app.world().resource::<Events<DespawnParallaxEvent>>();
+        let sent: Vec<&DespawnParallaxEvent> = events.iter_current_update_events().collect();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].camera, camera);
+    }
+
+    #[test]
+    fn test_parallax_background_reflect() {
+        let registry = AppTypeRegistry::default();
+        registry.write().register::<ParallaxBackground>();
+        let registry = registry.read();
+        let reflect_component = registry.get_type_data::<ReflectComponent>(TypeId::of::<ParallaxBackground>()).unwrap();
+
+        // Scenes insert the component from its dynamic representation
+        let background = ParallaxBackground::new(vec![LayerData {
+            path: "clouds.png".to_string(),
+            ..default()
+        }]);
+        let mut world = World::new();
+        let camera = world.spawn_empty().id();
+        reflect_component.insert(&mut world.entity_mut(camera), &*background.clone_value(), &registry);
+        assert_eq!(world.get::<ParallaxBackground>(camera).unwrap().layers[0].path, "clouds.png");
+        assert!(world.get::<ParallaxCameraComponent>(camera).is_some());
+    }
+
+    #[test]
+    fn test_texture_count() {
+        let layer = LayerData {
+            repeat: LayerRepeat::horizontally(RepeatStrategy::Same),
+            tile_size: UVec2::new(100, 50),
+            ..default()
+        };
+        assert_eq!(layer.texture_count(Vec2::new(300., 400.)), Vec2::new(10., 1.));
+        // Bigger windows need more textures to be covered
+        assert_eq!(layer.texture_count(Vec2::new(600., 800.)), Vec2::new(20., 1.));
+
+        let layer = LayerData {
+            repeat: LayerRepeat::both(RepeatStrategy::Same),
+            tile_size: UVec2::new(100, 50),
+            scale: Vec2::splat(2.),
+            ..default()
+        };
+        assert_eq!(layer.texture_count(Vec2::new(300., 400.)), Vec2::new(6., 10.));
+    }
+
+    /// Spawn a root with a single layer, returning the layer entity
+    fn spawn_layer(app: &mut App, camera: Entity) -> Entity {
+        let texture = app.world_mut().spawn_empty().id();
+        let layer = app
+            .world_mut()
+            .spawn(LayerComponent::new(camera))
+            .add_child(texture)
+            .id();
+        app.world_mut().spawn(ParallaxRoot::new(camera)).add_child(layer);
+        layer
+    }
+
+    #[test]
+    fn test_despawn_parallax() {
+        let mut app = App::new();
+        app.add_systems(Update, despawn_parallax_system);
+        let camera = app.world_mut().spawn(ParallaxCameraComponent::default()).id();
+        let other_camera = app.world_mut().spawn(ParallaxCameraComponent::default()).id();
+        let other_layer = spawn_layer(&mut app, other_camera);
+
+        // Layers are cleaned up with their camera
+        let layer = spawn_layer(&mut app, camera);
+        app.world_mut().entity_mut(camera).remove::<ParallaxCameraComponent>();
+        app.world_mut().despawn(other_camera);
+        app.update();
+        assert!(app.world().get_entity(layer).is_err());
+        assert!(app.world().get_entity(other_layer).is_err());
+    }
 }
@@ -2,11 +2,13 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct SpriteFrameUpdate {
     pub index: usize,
     pub total: usize,
     pub timer: Timer,
+    /// Duration of every frame, empty when all the frames last the timer duration
+    pub durations: Vec<Duration>,
 }
 
 impl SpriteFrameUpdate {
@@ -14,6 +16,10 @@ impl SpriteFrameUpdate {
         self.timer.tick(duration);
         if self.timer.just_finished() {
             self.index += 1;
+            if !self.durations.is_empty() {
+                let duration = self.durations[self.index % self.durations.len()];
+                self.timer.set_duration(duration);
+            }
         }
         self.index % self.total
     }
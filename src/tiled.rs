@@ -0,0 +1,410 @@
+//! Import of [Tiled](https://www.mapeditor.org/) image layers as parallax layers.
+//!
+//! Tiled's `parallaxx`/`parallaxy` factors describe how much a layer moves with the world,
+//! where `1.0` is a regular layer and `0.0` a layer fixed to the camera. This crate uses the
+//! opposite convention, so the imported [`LayerSpeed`] is `1.0 - parallax` on each axis.
+//!
+//! Positions are relative to the top-left corner of the map, with the y axis pointing up.
+//!
+//! The `repeatx`/`repeaty` flags are mapped to a [`LayerRepeat`], but parallax layers always repeat in at least one
+//! direction: image layers that don't repeat at all are imported as horizontally repeating layers, and a warning is logged.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::{CreateParallaxEvent, LayerData, LayerRepeat, LayerSpeed, ParallaxAsset, RepeatStrategy};
+
+#[derive(Debug, Error)]
+pub enum TiledError {
+    #[error("invalid tmx file: {0}")]
+    Xml(#[from] roxmltree::Error),
+    #[error("invalid tmj file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("image layer `{layer}` is missing the `{attribute}` attribute")]
+    MissingAttribute { layer: String, attribute: &'static str },
+    #[error("invalid value `{value}` for attribute `{attribute}`")]
+    InvalidAttribute { attribute: &'static str, value: String },
+    #[error("invalid tint color `{0}`")]
+    InvalidColor(String),
+}
+
+/// Image layer properties shared by the tmx and tmj formats
+#[derive(Debug, Clone)]
+struct ImageLayer {
+    image: String,
+    image_size: UVec2,
+    offset: Vec2,
+    parallax: Vec2,
+    repeat: (bool, bool),
+    tint: LinearRgba,
+}
+
+impl ImageLayer {
+    fn to_layer_data(&self, z: f32) -> LayerData {
+        let speed = Vec2::ONE - self.parallax;
+        let repeat = match self.repeat {
+            (false, true) => LayerRepeat::vertically(RepeatStrategy::Same),
+            (true, true) => LayerRepeat::both(RepeatStrategy::Same),
+            (true, false) => LayerRepeat::horizontally(RepeatStrategy::Same),
+            // Layers always repeat in at least one direction
+            (false, false) => {
+                warn!("Tiled image layer `{}` does not repeat, it is imported as a horizontally repeating layer", self.image);
+                LayerRepeat::horizontally(RepeatStrategy::Same)
+            }
+        };
+        let size = self.image_size.as_vec2();
+        LayerData {
+            speed: LayerSpeed::Bidirectional(speed.x, speed.y),
+            repeat,
+            path: self.image.clone(),
+            tile_size: self.image_size,
+            z,
+            position: Vec2::new(self.offset.x + size.x / 2., -(self.offset.y + size.y / 2.)),
+            color: self.tint.into(),
+            ..default()
+        }
+    }
+}
+
+/// Group properties inherited by nested layers
+#[derive(Debug, Clone, Copy)]
+struct Group {
+    offset: Vec2,
+    parallax: Vec2,
+    tint: LinearRgba,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            parallax: Vec2::ONE,
+            tint: LinearRgba::WHITE,
+        }
+    }
+}
+
+impl Group {
+    fn child(&self, offset: Vec2, parallax: Vec2, tint: LinearRgba) -> Self {
+        Self {
+            offset: self.offset + offset,
+            parallax: self.parallax * parallax,
+            tint: LinearRgba::from_vec4(self.tint.to_vec4() * tint.to_vec4()),
+        }
+    }
+}
+
+/// Parse a Tiled `#RRGGBB` or `#AARRGGBB` color together with the layer opacity
+fn parse_tint(tint: Option<&str>, opacity: f32) -> Result<LinearRgba, TiledError> {
+    let color = match tint {
+        Some(tint) => {
+            let hex = tint.trim_start_matches('#');
+            let (alpha, rgb) = match hex.len() {
+                6 => ("ff", hex),
+                8 => hex.split_at(2),
+                _ => return Err(TiledError::InvalidColor(tint.to_string())),
+            };
+            Srgba::hex(format!("{}{}", rgb, alpha)).map_err(|_| TiledError::InvalidColor(tint.to_string()))?
+        }
+        None => Srgba::WHITE,
+    };
+    Ok(LinearRgba::from(color.with_alpha(color.alpha * opacity)))
+}
+
+fn collect_layers(layers: &[ImageLayer]) -> Vec<LayerData> {
+    layers
+        .iter()
+        .enumerate()
+        .map(|(i, layer)| layer.to_layer_data(i as f32))
+        .collect()
+}
+
+fn xml_optional_attribute<T: std::str::FromStr>(node: roxmltree::Node, attribute: &'static str) -> Result<Option<T>, TiledError> {
+    node.attribute(attribute)
+        .map(|value| {
+            value.parse().map_err(|_| TiledError::InvalidAttribute {
+                attribute,
+                value: value.to_string(),
+            })
+        })
+        .transpose()
+}
+
+fn xml_attribute<T: std::str::FromStr>(node: roxmltree::Node, attribute: &'static str, default: T) -> Result<T, TiledError> {
+    Ok(xml_optional_attribute(node, attribute)?.unwrap_or(default))
+}
+
+fn xml_flag(node: roxmltree::Node, attribute: &'static str, default: bool) -> Result<bool, TiledError> {
+    Ok(xml_attribute::<u8>(node, attribute, default as u8)? != 0)
+}
+
+fn read_xml_layers(node: roxmltree::Node, group: Group, layers: &mut Vec<ImageLayer>) -> Result<(), TiledError> {
+    for child in node.children().filter(|child| child.is_element()) {
+        let tag = child.tag_name().name();
+        if (tag != "imagelayer" && tag != "group") || !xml_flag(child, "visible", true)? {
+            continue;
+        }
+        let name = child.attribute("name").unwrap_or_default().to_string();
+        let offset = Vec2::new(xml_attribute(child, "offsetx", 0.)?, xml_attribute(child, "offsety", 0.)?);
+        let parallax = Vec2::new(xml_attribute(child, "parallaxx", 1.)?, xml_attribute(child, "parallaxy", 1.)?);
+        let tint = parse_tint(child.attribute("tintcolor"), xml_attribute(child, "opacity", 1.)?)?;
+        let group = group.child(offset, parallax, tint);
+        if tag == "group" {
+            read_xml_layers(child, group, layers)?;
+            continue;
+        }
+        let Some(image) = child.children().find(|node| node.has_tag_name("image")) else {
+            continue;
+        };
+        let missing = |attribute| TiledError::MissingAttribute {
+            layer: name.clone(),
+            attribute,
+        };
+        layers.push(ImageLayer {
+            image: image.attribute("source").ok_or_else(|| missing("source"))?.to_string(),
+            image_size: UVec2::new(
+                xml_optional_attribute(image, "width")?.ok_or_else(|| missing("width"))?,
+                xml_optional_attribute(image, "height")?.ok_or_else(|| missing("height"))?,
+            ),
+            offset: group.offset,
+            parallax: group.parallax,
+            repeat: (xml_flag(child, "repeatx", false)?, xml_flag(child, "repeaty", false)?),
+            tint: group.tint,
+        });
+    }
+    Ok(())
+}
+
+/// Create layers from the image layers of a Tiled `.tmx` map
+pub fn layers_from_tmx(tmx: &str) -> Result<Vec<LayerData>, TiledError> {
+    let document = roxmltree::Document::parse(tmx)?;
+    let mut layers = Vec::new();
+    read_xml_layers(document.root_element(), Group::default(), &mut layers)?;
+    Ok(collect_layers(&layers))
+}
+
+#[derive(Deserialize)]
+struct JsonMap {
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+#[derive(Deserialize)]
+struct JsonLayer {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default = "default_true")]
+    visible: bool,
+    #[serde(default)]
+    offsetx: f32,
+    #[serde(default)]
+    offsety: f32,
+    #[serde(default = "default_one")]
+    parallaxx: f32,
+    #[serde(default = "default_one")]
+    parallaxy: f32,
+    #[serde(default = "default_one")]
+    opacity: f32,
+    tintcolor: Option<String>,
+    #[serde(default)]
+    repeatx: bool,
+    #[serde(default)]
+    repeaty: bool,
+    image: Option<String>,
+    imagewidth: Option<u32>,
+    imageheight: Option<u32>,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_one() -> f32 {
+    1.
+}
+
+fn read_json_layers(json_layers: &[JsonLayer], group: Group, layers: &mut Vec<ImageLayer>) -> Result<(), TiledError> {
+    for layer in json_layers {
+        if (layer.kind != "imagelayer" && layer.kind != "group") || !layer.visible {
+            continue;
+        }
+        let group = group.child(
+            Vec2::new(layer.offsetx, layer.offsety),
+            Vec2::new(layer.parallaxx, layer.parallaxy),
+            parse_tint(layer.tintcolor.as_deref(), layer.opacity)?,
+        );
+        if layer.kind == "group" {
+            read_json_layers(&layer.layers, group, layers)?;
+            continue;
+        }
+        let Some(image) = layer.image.as_ref().filter(|image| !image.is_empty()) else {
+            continue;
+        };
+        let missing = |attribute| TiledError::MissingAttribute {
+            layer: layer.name.clone(),
+            attribute,
+        };
+        layers.push(ImageLayer {
+            image: image.clone(),
+            image_size: UVec2::new(
+                layer.imagewidth.ok_or_else(|| missing("imagewidth"))?,
+                layer.imageheight.ok_or_else(|| missing("imageheight"))?,
+            ),
+            offset: group.offset,
+            parallax: group.parallax,
+            repeat: (layer.repeatx, layer.repeaty),
+            tint: group.tint,
+        });
+    }
+    Ok(())
+}
+
+/// Create layers from the image layers of a Tiled `.tmj` map
+pub fn layers_from_tmj(tmj: &str) -> Result<Vec<LayerData>, TiledError> {
+    let map: JsonMap = serde_json::from_str(tmj)?;
+    let mut layers = Vec::new();
+    read_json_layers(&map.layers, Group::default(), &mut layers)?;
+    Ok(collect_layers(&layers))
+}
+
+impl CreateParallaxEvent {
+    /// Create a parallax event from a Tiled `.tmx` map
+    pub fn from_tmx(tmx: &str, camera: Entity) -> Result<Self, TiledError> {
+        Ok(Self {
+            layers_data: layers_from_tmx(tmx)?,
+            camera,
+        })
+    }
+
+    /// Create a parallax event from a Tiled `.tmj` map
+    pub fn from_tmj(tmj: &str, camera: Entity) -> Result<Self, TiledError> {
+        Ok(Self {
+            layers_data: layers_from_tmj(tmj)?,
+            camera,
+        })
+    }
+}
+
+/// Loads a [`ParallaxAsset`] from the image layers of a Tiled map.
+/// Image paths are resolved relative to the map file.
+#[derive(Default)]
+pub struct TiledParallaxAssetLoader;
+
+#[derive(Debug, Error)]
+pub enum TiledParallaxAssetLoaderError {
+    #[error("could not read tiled map: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not read tiled map: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    #[error(transparent)]
+    Tiled(#[from] TiledError),
+    #[error("invalid image path: {0}")]
+    Path(#[from] bevy::asset::ParseAssetPathError),
+}
+
+impl AssetLoader for TiledParallaxAssetLoader {
+    type Asset = ParallaxAsset;
+    type Settings = ();
+    type Error = TiledParallaxAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let text = std::str::from_utf8(&bytes)?;
+        let mut layers = match load_context.path().extension().and_then(|extension| extension.to_str()) {
+            Some("tmj") | Some("json") => layers_from_tmj(text)?,
+            _ => layers_from_tmx(text)?,
+        };
+        for layer in layers.iter_mut() {
+            layer.path = load_context.asset_path().resolve_embed(&layer.path)?.to_string();
+        }
+        Ok(ParallaxAsset {
+            name: load_context
+                .path()
+                .file_stem()
+                .map(|name| name.to_string_lossy().to_string()),
+            layers,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx", "tmj"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{layers_from_tmj, layers_from_tmx};
+    use crate::LayerSpeed;
+
+    const TMX: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="10" height="10" tilewidth="16" tileheight="16">
+ <imagelayer id="1" name="sky" parallaxx="0" parallaxy="0" repeatx="1" repeaty="1">
+  <image source="sky.png" width="64" height="32"/>
+ </imagelayer>
+ <group id="2" name="far" offsetx="10" parallaxx="0.5" opacity="0.5">
+  <imagelayer id="3" name="hills" offsety="20" parallaxx="0.5" repeatx="1" tintcolor="#ff0000">
+   <image source="hills.png" width="100" height="50"/>
+  </imagelayer>
+ </group>
+ <imagelayer id="4" name="hidden" visible="0">
+  <image source="hidden.png" width="1" height="1"/>
+ </imagelayer>
+</map>"##;
+
+    const TMJ: &str = r##"{
+  "layers": [
+    { "type": "imagelayer", "name": "sky", "image": "sky.png", "imagewidth": 64, "imageheight": 32,
+      "parallaxx": 0, "parallaxy": 0, "repeatx": true, "repeaty": true },
+    { "type": "group", "name": "far", "offsetx": 10, "parallaxx": 0.5, "opacity": 0.5, "layers": [
+      { "type": "imagelayer", "name": "hills", "image": "hills.png", "imagewidth": 100, "imageheight": 50,
+        "offsety": 20, "parallaxx": 0.5, "repeatx": true, "tintcolor": "#ff0000" }
+    ]},
+    { "type": "tilelayer", "name": "ground" }
+  ]
+}"##;
+
+    fn check_layers(layers: Vec<crate::LayerData>) {
+        assert_eq!(layers.len(), 2);
+
+        let sky = &layers[0];
+        assert_eq!(sky.path, "sky.png");
+        assert_eq!(sky.tile_size, UVec2::new(64, 32));
+        assert!(matches!(sky.speed, LayerSpeed::Bidirectional(x, y) if x == 1. && y == 1.));
+        assert!(sky.repeat.has_horizontal() && sky.repeat.has_vertical());
+        assert_eq!(sky.position, Vec2::new(32., -16.));
+        assert_eq!(sky.z, 0.);
+
+        let hills = &layers[1];
+        assert!(matches!(hills.speed, LayerSpeed::Bidirectional(x, y) if x == 0.75 && y == 0.));
+        assert!(hills.repeat.has_horizontal() && !hills.repeat.has_vertical());
+        assert_eq!(hills.position, Vec2::new(60., -45.));
+        assert_eq!(hills.color, Color::LinearRgba(LinearRgba::new(1., 0., 0., 0.5)));
+        assert_eq!(hills.z, 1.);
+    }
+
+    #[test]
+    fn test_layers_from_tmx() {
+        check_layers(layers_from_tmx(TMX).unwrap());
+    }
+
+    #[test]
+    fn test_layers_from_tmj() {
+        check_layers(layers_from_tmj(TMJ).unwrap());
+    }
+}